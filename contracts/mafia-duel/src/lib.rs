@@ -10,18 +10,29 @@
 //!      Contract recomputes sha256(target||nonce) and rejects mismatches (binding property).
 //!   3. resolve() — executes verified actions; AI uses deterministic PRNG.
 //!
+//! Nonces can be derived instead of stored: the client signs `nonce_message(session_id, day, phase)`
+//! with the player's key and uses the first 8 bytes (big-endian) of sha256(signature) as the nonce.
+//! The signature never leaves the client, so hiding holds, and it can always be re-derived.
+//!
 //! Roles: 2 Mafia | 1 Doctor | 1 Sheriff | 4 Villager
+//!        (RULE_VIGILANTE swaps one Villager for a Vigilante, RULE_CONFIDANT another for a Confidant)
 //! Win:   Town wins when no Mafia remain. Mafia wins when Mafia >= Town.
 
 use soroban_sdk::{
-    contract, contractclient, contracterror, contractimpl, contracttype,
-    Address, Bytes, BytesN, Env, Vec,
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype,
+    token, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, TryFromVal, Val, Vec,
 };
 
 pub const ROLE_MAFIA: u32    = 0;
 pub const ROLE_VILLAGER: u32 = 1;
 pub const ROLE_DOCTOR: u32   = 2;
 pub const ROLE_SHERIFF: u32  = 3;
+pub const ROLE_VIGILANTE: u32 = 4;
+pub const ROLE_SERIAL_KILLER: u32 = 5;
+/// Town member told one mafia member's slot at game start; see `get_confidant_intel`.
+pub const ROLE_CONFIDANT: u32 = 6;
+/// Role of a slot left unused by a game with fewer than MAX_PLAYERS seats; such slots stay dead.
+pub const ROLE_NONE: u32 = u32::MAX;
 
 pub const PHASE_LOBBY: u32        = 0;
 pub const PHASE_NIGHT_COMMIT: u32 = 1;
@@ -29,24 +40,209 @@ pub const PHASE_NIGHT_REVEAL: u32 = 2;
 pub const PHASE_DAY: u32          = 3;
 pub const PHASE_OVER: u32         = 4;
 
+/// `session_status` codes.
+pub const STATUS_NONEXISTENT: u32 = 0;
+pub const STATUS_LOBBY: u32       = 1;
+pub const STATUS_IN_PROGRESS: u32 = 2;
+pub const STATUS_OVER: u32        = 3;
+
 pub const TEAM_MAFIA: u32 = 0;
 pub const TEAM_TOWN: u32  = 1;
+/// Neutral faction: the serial killer wins alone.
+pub const TEAM_SERIAL_KILLER: u32 = 2;
 
 pub const MAX_PLAYERS: u32      = 8;
 pub const GAME_TTL_LEDGERS: u32 = 518_400;
 pub const PASS_TARGET: u32      = u32::MAX;
+pub const MAX_LAST_WORDS: u32   = 280;
+pub const ROLE_COUNT: u32       = 7;
+pub const NONCE_DOMAIN: &[u8]   = b"mafia-duel:nonce";
+/// TTL of a session's creation marker, refreshed with every write of the game so it always outlives it.
+pub const CREATED_TTL_LEDGERS: u32 = 2 * GAME_TTL_LEDGERS;
+/// Most ledgers a host may add to a single phase's deadline (~1 day).
+pub const MAX_DEADLINE_EXTENSION: u32 = 17_280;
+/// Deepest Merkle path accepted by `reveal_action_merkle` (256 leaves).
+pub const MAX_MERKLE_DEPTH: u32 = 8;
+/// Leaf position of the night target in a Merkle commitment; the other leaves stay client-side.
+pub const ACTION_COMPONENT: u32 = 0;
+/// Days `auto_play_remaining` will simulate before giving up on a stalemate.
+pub const MAX_AUTO_DAYS: u32 = 16;
+/// Total ledgers a creator may keep one game paused (~2 days); a longer pause lapses on its own.
+pub const MAX_PAUSE_LEDGERS: u32 = 34_560;
+pub const MAX_OBSERVERS: u32     = 16;
+pub const MAX_SEALED_NOTE: u32   = 512;
+pub const MAX_BOT_NAME: u32      = 32;
+pub const BPS_DENOMINATOR: u32   = 10_000;
+/// Most lobbies one `create_bracket` call may open.
+pub const MAX_BRACKET: u32       = 16;
+/// Ledgers past its grace deadline after which a stalled game counts as abandoned (~1 day).
+pub const ABANDON_LEDGERS: u32   = 17_280;
+/// Longest best-of-N series `create_match` accepts.
+pub const MAX_MATCH_ROUNDS: u32  = 9;
+/// Most active sessions one `resolvable_sessions` call inspects.
+pub const MAX_RESOLVE_SCAN: u32  = 32;
+
+pub const DEATH_NIGHT_KILL: u32 = 0;
+pub const DEATH_LYNCH: u32      = 1;
+pub const DEATH_VIGILANTE: u32  = 2;
+pub const DEATH_BACKFIRE: u32   = 3;
+pub const DEATH_SERIAL_KILL: u32 = 4;
+
+/// What `ActionState::next_step` asks of a seat.
+pub const STEP_NONE: u32   = 0;
+pub const STEP_COMMIT: u32 = 1;
+pub const STEP_REVEAL: u32 = 2;
+pub const STEP_VOTE: u32   = 3;
+
+/// Day-vote tie breakers for `GameConfig::tie_breaker`.
+pub const TIE_FIRST_SLOT: u32 = 0;
+pub const TIE_NO_ELIM: u32    = 1;
+pub const TIE_RANDOM: u32     = 2;
+/// Tied top candidates go to a restricted re-vote; a tied runoff eliminates no one.
+pub const TIE_RUNOFF: u32     = 3;
+/// A tied slot the sheriff last found to be mafia goes first; any remaining picks are random.
+pub const TIE_SUSPECT: u32    = 4;
+pub const MAX_RUNOFF_ROUNDS: u32 = 1;
+
+/// How `GameConfig::mafia_tie_breaker` settles mafia members naming different night targets.
+pub const MAFIA_TIE_RANDOM: u32  = 0;
+/// Any disagreement and no one dies.
+pub const MAFIA_TIE_NO_KILL: u32 = 1;
+/// The lowest-slot mafia member with a target decides.
+pub const MAFIA_TIE_FIRST: u32   = 2;
+
+/// When `GameConfig::stake_timing` moves each human's wager into escrow, in the admin's fee token.
+/// Escrowed pots are paid out as `get_payouts` splits them when the game ends.
+pub const STAKE_NONE: u32     = 0;
+/// Each human pays on taking a seat, and is refunded on `leave_game`, `cancel_game`, a
+/// `swap_address` away, or the option being switched off. Anyone seated earlier pays at
+/// `begin_game`. Cancel an unwanted lobby rather than letting it lapse with stakes inside.
+pub const STAKE_ON_JOIN: u32  = 1;
+/// `begin_game` pulls every human's wager from the allowance they granted this contract, all or
+/// nothing: any shortfall aborts the start.
+pub const STAKE_ON_BEGIN: u32 = 2;
+
+/// Rule flags for `GameConfig::rules`.
+pub const RULE_TEAM_KILL: u32             = 1 << 0;
+/// Replace one villager with a vigilante, who dies of guilt after shooting a town member.
+pub const RULE_VIGILANTE: u32             = 1 << 1;
+/// A doctor protecting the vigilante also spares them from that night's backfire.
+pub const RULE_VIG_BACKFIRE_SAVABLE: u32  = 1 << 2;
+/// Each villager may once per game watch a player at night to learn whether they acted.
+pub const RULE_WATCHER: u32               = 1 << 3;
+/// Replace one villager with a neutral serial killer who kills nightly and wins alone.
+pub const RULE_SERIAL_KILLER: u32         = 1 << 4;
+/// A living serial killer keeps mafia from winning at parity; the game runs on until they are dead.
+pub const RULE_NEUTRAL_BLOCKS_PARITY: u32 = 1 << 5;
+/// Hold each night's outcome back from `get_game_summary` until the host calls `announce`.
+pub const RULE_MORNING_ANNOUNCE: u32      = 1 << 6;
+/// Scale each hub player's points by their role's difficulty. The points are public, so this
+/// tells hub observers something about the two reported seats' roles.
+pub const RULE_ROLE_POINTS: u32           = 1 << 7;
+/// Record every reveal for `get_reveal_transcript`, readable once the game is over.
+pub const RULE_TRANSCRIPT: u32            = 1 << 8;
+/// Shuffle which player sits in which slot at `begin_game`, so the creator isn't always slot 0.
+pub const RULE_SHUFFLE_SEATS: u32         = 1 << 9;
+/// Bots post decoy commitments and show as submitted during night phases, like humans who acted.
+/// Only submission status is masked: seats stay public, so `get_players` and `get_game` still
+/// show every bot seat with `addr: None`.
+pub const RULE_MASK_BOTS: u32             = 1 << 10;
+/// Once no human is alive, one `resolve` call plays the bots through to the end.
+pub const RULE_AUTO_ADVANCE: u32          = 1 << 11;
+/// Day votes stay out of `get_day_votes_live` until the tally; only the result is shown.
+pub const RULE_BLIND_DAY: u32             = 1 << 12;
+/// Publish a lynched player's role in their grave.
+pub const RULE_REVEAL_ON_LYNCH: u32       = 1 << 13;
+/// Publish the role of anyone who dies at night (mafia, vigilante, serial killer or backfire).
+pub const RULE_REVEAL_ON_NIGHTKILL: u32   = 1 << 14;
+/// AI day votes follow a sheriff's public mafia finding: town leans toward it, mafia steer clear.
+pub const RULE_AI_SUSPICION: u32          = 1 << 15;
+/// Mafia may not reveal PASS at night; a mafia committed to PASS simply can't reveal, and a night
+/// no mafia revealed a target for gets an AI-drawn kill. Without it every mafia may pass, and AI
+/// mafia treat PASS as one more option.
+pub const RULE_MAFIA_MUST_KILL: u32       = 1 << 16;
+/// Party mode: only the mafia act at night. Every other role is still dealt but may only PASS.
+pub const RULE_NO_POWERS: u32             = 1 << 18;
+/// Open variant: anyone may read how many of each role are still alive.
+pub const RULE_OPEN_COUNTS: u32           = 1 << 19;
+/// Standard order checks for a winner straight after the night, so a kill that reaches parity
+/// ends the game before anyone votes. With this rule only a town win ends a night; mafia and
+/// serial killer wins wait until the following day's lynch has resolved.
+pub const RULE_DAY_BEFORE_PARITY: u32     = 1 << 20;
+/// Teaching mode: every night's targets and the sheriff's result are published via `get_night_reports`.
+pub const RULE_OPEN_NIGHT: u32            = 1 << 21;
+/// Day votes are counted in public but never attributed: getters hide who voted for whom.
+pub const RULE_ANONYMOUS_VOTES: u32       = 1 << 22;
+/// A sheriff who dies during the night gets no result for it; by default the check still stands.
+pub const RULE_STRICT_SHERIFF: u32        = 1 << 23;
+/// A human who lets AFK_PHASE_LIMIT phases in a row be force-resolved without acting is
+/// handed to the AI until they act again; the game stops waiting on them.
+pub const RULE_AFK_BOTIFY: u32            = 1 << 24;
+/// Mafia play blind: `get_mafia_team` tells them nothing, and since refusing a target would give
+/// partners away, the mafia kill may land on a teammate as under RULE_TEAM_KILL.
+pub const RULE_MAFIA_ANONYMOUS: u32       = 1 << 25;
+/// Hard mode: a doctor who protects someone nobody attacked risks a DOCTOR_EXPOSE_PCT chance of
+/// being named in `Game::doctor_exposed`. Successful saves are never at risk.
+pub const RULE_DOCTOR_EXPOSURE: u32       = 1 << 26;
+/// Replace one villager with a confidant, who starts the game knowing one mafia member.
+pub const RULE_CONFIDANT: u32             = 1 << 27;
+/// Judgment: the day's vote only accuses. The day stays open for a guilty/innocent vote on the
+/// accused (a vote for an accused slot is guilty, PASS innocent), and an accused is lynched only
+/// if a strict majority of the living votes guilty. One judgment per day.
+pub const RULE_JUDGMENT: u32              = 1 << 28;
 
-const ROLE_TEMPLATE: [u32; 8] = [
-    ROLE_MAFIA, ROLE_MAFIA,
-    ROLE_DOCTOR, ROLE_SHERIFF,
-    ROLE_VILLAGER, ROLE_VILLAGER, ROLE_VILLAGER, ROLE_VILLAGER,
-];
+/// Under RULE_AFK_BOTIFY, consecutive missed phases before a human is played by the AI.
+pub const AFK_PHASE_LIMIT: u32 = 3;
+
+/// Under RULE_AI_SUSPICION, how many ballot entries the suspected slot counts as for AI town.
+pub const AI_SUSPICION_WEIGHT: u32 = 4;
+/// Under RULE_DOCTOR_EXPOSURE, percent chance a wasted save exposes the doctor.
+pub const DOCTOR_EXPOSE_PCT: u64 = 25;
+
+/// Percent of the wager staked per role under RULE_ROLE_POINTS, indexed by role.
+const ROLE_POINT_PCT: [i128; ROLE_COUNT as usize] = [150, 100, 120, 120, 130, 200, 110];
+
+/// Smallest table `GameConfig::player_count` allows.
+pub const MIN_PLAYERS: u32 = 4;
 
 #[contracttype]
+#[derive(Clone)]
 pub enum DataKey {
     Game(u32),
     Admin,
     GameHubAddress,
+    Stats(Address),
+    Transcript(u32),
+    BalanceStats,
+    WagerLimits,
+    SealedNote(u32, u32),
+    DeathHook,
+    NextSession,
+    /// Session id -> the creator `reserve_session` handed it to, until they create with it.
+    Reserved(u32),
+    Match(u32),
+    RoleStats,
+    Finalized(u32),
+    SinglePlayerAllowed,
+    /// Positions in use in the active-session index; see `set_active`.
+    ActiveCount,
+    /// Index position -> session id, and back. Temporary, refreshed with the game, so a lapsed
+    /// game's entries lapse with it.
+    ActiveAt(u32),
+    ActivePos(u32),
+    /// Whether last night's save target was attacked at all; absent if no save was made. Kept off
+    /// `Game` so only `get_doctor_feedback` shows it.
+    DoctorFeedback(u32),
+    /// Under RULE_CONFIDANT: the mafia slot the confidant was shown; only `get_confidant_intel` reads it.
+    ConfidantIntel(u32),
+    /// Under RULE_WATCHER: (session, slot) -> that watcher's latest peek; only `get_peek_result` reads it.
+    Peek(u32, u32),
+    /// Persistent marker that a session was created, so a lapsed game reads as GameExpired.
+    Created(u32),
+    Fees,
+    Treasury,
+    /// The public record `finalize` keeps beside the `FinalizedGame`; see `full_sync`.
+    FinalSync(u32),
 }
 
 #[contracttype]
@@ -58,6 +254,208 @@ pub struct Slot {
     pub action: Option<u32>,
     pub submitted: bool,
     pub commitment: Option<BytesN<32>>,
+    pub silent_since: u32,
+    /// Uses left of the seat's limited ability (the watcher's peek); see `GameConfig::ability_charges`.
+    pub ability_charges: u32,
+    /// Cosmetic name the creator gave this bot seat; cleared if a human takes the seat.
+    pub bot_name: Option<Bytes>,
+    /// Set by `lock_commitment`: tonight's commitment can no longer be replaced.
+    pub locked: bool,
+    /// Phases in a row this human let resolve without acting; reset when they act.
+    pub missed_phases: u32,
+    /// Under RULE_AFK_BOTIFY: the AI plays this seat until its human acts again.
+    pub afk: bool,
+    /// This seat's wager is in escrow; see `GameConfig::stake_timing`.
+    pub staked: bool,
+}
+
+/// Mirror of the encoding `pub const`s, for clients that would rather fetch than hardcode them.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constants {
+    pub pass_target: u32,
+    pub max_players: u32,
+    pub min_players: u32,
+    pub role_mafia: u32,
+    pub role_villager: u32,
+    pub role_doctor: u32,
+    pub role_sheriff: u32,
+    pub role_vigilante: u32,
+    pub role_serial_killer: u32,
+    pub role_confidant: u32,
+    pub role_none: u32,
+    pub phase_lobby: u32,
+    pub phase_night_commit: u32,
+    pub phase_night_reveal: u32,
+    pub phase_day: u32,
+    pub phase_over: u32,
+    pub team_mafia: u32,
+    pub team_town: u32,
+    pub team_serial_killer: u32,
+}
+
+/// A best-of-`rounds` series: one game per round, all seating `players` in order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Match {
+    pub players: Vec<Address>,
+    pub rounds: u32,
+    /// Rounds won, indexed like `players`. A round counts for every player on the winning team.
+    pub wins: Vec<u32>,
+    /// Session id of each round so far; the last one is in play unless the match is over.
+    pub sessions: Vec<u32>,
+    pub over: bool,
+}
+
+/// Who sits in a slot: a human's address, or a bot and its optional name.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Seat {
+    pub addr: Option<Address>,
+    pub bot_name: Option<Bytes>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Peek {
+    pub target: u32,
+    pub acted: bool,
+}
+
+/// One opened commitment, recomputable by anyone: plain reveals (empty `path`) hash
+/// sha256(target||nonce); Merkle reveals fold the leaf sha256(index||target||nonce) up `path`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevealProof {
+    pub day: u32,
+    pub slot: u32,
+    pub commitment: BytesN<32>,
+    pub target: u32,
+    pub nonce: u64,
+    pub path: Vec<BytesN<32>>,
+    pub index: u32,
+}
+
+/// Everything one seat needs to choose its next move; see `decision_space`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecisionSpace {
+    pub role: u32,
+    pub phase: u32,
+    /// Empty when the seat has nothing to do this phase (dead, already acted, or no phase open).
+    pub legal_targets: Vec<u32>,
+    pub can_pass: bool,
+    /// One-shot abilities still unspent (the watcher's peek).
+    pub abilities_left: u32,
+}
+
+/// The caller's own commit-reveal progress; see `my_action_state`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionState {
+    pub phase: u32,
+    /// A commitment is on file for tonight.
+    pub committed: bool,
+    /// Tonight's commitment has been opened with `reveal_action`.
+    pub revealed: bool,
+    pub locked: bool,
+    pub ability_charges: u32,
+    /// STEP_* the seat owes next; STEP_NONE once it has acted or has nothing to do.
+    pub next_step: u32,
+}
+
+/// One entry per death, in the order players died.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grave {
+    pub slot: u32,
+    pub day: u32,
+    pub cause: u32,
+    pub last_words: Option<Bytes>,
+    /// The dead player's role, when the game's reveal rules publish it for this cause.
+    pub revealed_role: Option<u32>,
+}
+
+/// A public, unverified sheriff claim: anyone may claim anything, so roles stay hidden.
+#[contracttype]
+#[derive(Clone)]
+pub struct Claim {
+    pub claimer: u32,
+    pub day: u32,
+    pub target: u32,
+    pub is_mafia: bool,
+}
+
+/// Lifetime record for one address, kept in persistent storage. `roles_played` is indexed by role id.
+#[contracttype]
+#[derive(Clone)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub games_won: u32,
+    pub mafia_wins: u32,
+    pub town_wins: u32,
+    pub games_survived: u32,
+    pub roles_played: Vec<u32>,
+}
+
+/// Contract-wide role-balance counters across all games (saturating).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceStats {
+    pub kills_attempted: u32,
+    pub saves_succeeded: u32,
+    pub investigations: u32,
+    pub lynches: u32,
+}
+
+/// Admin-set charges in `token`, paid to the treasury: `create_fee` per lobby opened and
+/// `action_fee` per night commitment. Kept apart from wagers, so pots and payouts never see them.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fees {
+    pub token: Address,
+    pub create_fee: i128,
+    pub action_fee: i128,
+}
+
+/// Contract-wide outcome counters per role, indexed by role id (saturating). A game counts once
+/// per role dealt in it, however many seats held that role.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleStats {
+    /// Finished games in which the role was dealt.
+    pub games: Vec<u32>,
+    /// Of those, games the role's team won.
+    pub wins: Vec<u32>,
+}
+
+/// Per-game rule options, set by the creator while the game is in the lobby.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameConfig {
+    pub rules: u32,
+    /// Ledgers a silent human gets before `resolve` hands their seat to the AI; 0 = silent humans pass.
+    pub grace_ledgers: u32,
+    pub tie_breaker: u32,
+    /// Most-voted players lynched each day (1 = classic single lynch).
+    pub day_eliminations: u32,
+    /// Seats in play, MIN_PLAYERS..=MAX_PLAYERS; slots from here on are unused.
+    pub player_count: u32,
+    /// Basis points of the pot `get_payouts` hands back to losing humans; 0 = winner takes all.
+    pub consolation_bps: u32,
+    /// Uses of a limited ability every seat is dealt (at least 1).
+    pub ability_charges: u32,
+    /// Every this many days the charges refill to `ability_charges`; 0 = they never do.
+    pub recharge_every: u32,
+    /// Ledgers each day opens with for discussion only; votes and the day's grace clock start
+    /// after it. 0 = votes are accepted as soon as the day opens.
+    pub discussion_ledgers: u32,
+    /// Practice table: `ai_intentions` shows every bot's next move. Leave off for competitive play.
+    pub practice: bool,
+    /// MAFIA_TIE_* rule for a split mafia kill vote.
+    pub mafia_tie_breaker: u32,
+    /// STAKE_* choice of when wagers are escrowed; STAKE_NONE leaves them as hub points only.
+    pub stake_timing: u32,
 }
 
 #[contracttype]
@@ -73,12 +471,114 @@ pub struct Game {
     pub last_saved: bool,
     pub last_investigated: Option<u32>,
     pub invest_is_mafia: bool,
-    pub last_voted_out: Option<u32>,
+    pub last_voted_out: Vec<u32>,
     pub wager: i128,
+    pub config: GameConfig,
+    pub graveyard: Vec<Grave>,
+    pub claims: Vec<Claim>,
+    /// Non-empty while a day runoff is open; day votes must target one of these slots.
+    pub runoff_candidates: Vec<u32>,
+    pub runoff_round: u32,
+    /// Ledgers the host added to the current phase's grace deadline.
+    pub deadline_extension: u32,
+    /// Under RULE_MORNING_ANNOUNCE: the night has resolved but `announce` hasn't published it.
+    pub pending_announcement: bool,
+    pub pending_killed: Option<u32>,
+    pub pending_saved: bool,
+    /// Set by `pause_game`; game actions and `resolve` are refused until `resume_game`.
+    pub paused: bool,
+    pub paused_at: u32,
+    /// Ledgers already spent paused, capped at MAX_PAUSE_LEDGERS over the whole game.
+    pub pause_used: u32,
+    /// Spectators: no seat, no actions; they read `get_game_summary`.
+    pub observers: Vec<Address>,
+    /// Votes received per slot in the most recently tallied day vote (runoffs included).
+    pub last_tally: Vec<u32>,
+    /// One entry per announced night; `deaths == 0` marks a peaceful night.
+    pub night_log: Vec<NightRecord>,
+    /// Under RULE_OPEN_NIGHT only: the full detail of every resolved night.
+    pub night_reports: Vec<NightReport>,
+    /// Creator-fixed randomness from `set_rng_seed`; None derives it from the session id.
+    pub rng_seed: Option<BytesN<32>>,
+    /// Set for rounds of a `create_match` series; the hub then hears about the match, not the round.
+    pub match_id: Option<u32>,
+    /// Under RULE_DOCTOR_EXPOSURE: the doctor's slot, once a wasted save has given them away.
+    pub doctor_exposed: Option<u32>,
+    /// Ledger from which today's votes are accepted; see `GameConfig::discussion_ledgers`.
+    pub votes_open_at: u32,
+    /// Under RULE_JUDGMENT: slots on trial while the day's guilty/innocent vote is open.
+    pub accused: Vec<u32>,
+    /// Under RULE_OPEN_NIGHT: the role behind last night's first death (ROLE_MAFIA, ROLE_VIGILANTE
+    /// or ROLE_SERIAL_KILLER). None after a bloodless night and in hidden games.
+    pub last_kill_source: Option<u32>,
+    /// Token the escrowed stakes are held in, fixed by the first stake taken.
+    pub stake_token: Option<Address>,
+}
+
+/// Everything that was aimed at whom on one night; only recorded under RULE_OPEN_NIGHT.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NightReport {
+    pub day: u32,
+    pub mafia_target: Option<u32>,
+    pub doctor_target: Option<u32>,
+    pub sheriff_target: Option<u32>,
+    pub sheriff_found_mafia: bool,
+    pub vigilante_target: Option<u32>,
+    pub serial_killer_target: Option<u32>,
+}
+
+/// What `finalize` keeps of a finished game once the full `Game` is freed. Indexed by slot.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalizedGame {
+    pub winner: Option<u32>,
+    pub day: u32,
+    pub seats: Vec<Seat>,
+    pub roles: Vec<u32>,
+    /// sha256 of the XDR-encoded graveyard, for checking a replay or archived copy against.
+    pub history: BytesN<32>,
+    /// What `get_payouts` returned when the game was finalized.
+    pub payouts: Vec<(Address, i128)>,
+}
+
+/// Everything `full_sync` returns in one read.
+#[contracttype]
+#[derive(Clone)]
+pub struct FullSync {
+    pub summary: GameSummary,
+    pub graveyard: Vec<Grave>,
+    pub night_log: Vec<NightRecord>,
+    pub claims: Vec<Claim>,
+    pub last_tally: Vec<u32>,
+    pub known_roles: Vec<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NightRecord {
+    pub day: u32,
+    pub deaths: u32,
+}
+
+/// Public view of a game; night deaths stay hidden while an announcement is pending.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameSummary {
+    pub phase: u32,
+    pub day: u32,
+    pub winner: Option<u32>,
+    pub alive: Vec<u32>,
+    pub last_killed: Option<u32>,
+    pub last_saved: bool,
+    pub last_voted_out: Vec<u32>,
+    pub pending_announcement: bool,
+    /// See `Game::last_kill_source`; withheld until the night is announced.
+    pub last_kill_source: Option<u32>,
 }
 
 #[contracterror]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MafiaError {
     GameNotFound    = 1,
     GameFull        = 2,
@@ -93,6 +593,60 @@ pub enum MafiaError {
     SessionExists   = 11,
     InvalidReveal   = 12,
     NoCommitment    = 13,
+    NotYetReady     = 14,
+    TextTooLong     = 15,
+    StillAlive      = 16,
+    AbilityUsed     = 17,
+    LimitExceeded   = 18,
+    InvalidConfig   = 19,
+    GamePaused      = 20,
+    InvalidWager    = 21,
+    RuleDisabled    = 22,
+    Overflow        = 23,
+    NotEnoughPlayers = 24,
+    RevealWindowClosed = 25,
+    CommitWindowClosed = 26,
+    GameExpired     = 27,
+    InvalidCommitment = 28,
+    DiscussionOpen  = 29,
+    InsufficientFee = 30,
+    SeatLocked      = 31,
+    InsufficientStake = 32,
+}
+
+#[contractevent]
+pub struct DeadlineExtended {
+    #[topic]
+    pub session_id: u32,
+    pub deadline_ledger: u32,
+}
+
+#[contractevent]
+pub struct SessionCleared {
+    #[topic]
+    pub session_id: u32,
+}
+
+/// Host powers moved because the previous host died; `host` is the admin if no human survives.
+#[contractevent]
+pub struct HostChanged {
+    #[topic]
+    pub session_id: u32,
+    pub host: Address,
+}
+
+#[contractevent]
+pub struct GamePaused {
+    #[topic]
+    pub session_id: u32,
+    pub paused_at: u32,
+}
+
+#[contractevent]
+pub struct GameResumed {
+    #[topic]
+    pub session_id: u32,
+    pub paused_for: u32,
 }
 
 #[contractclient(name = "GameHubClient")]
@@ -109,6 +663,12 @@ pub trait GameHub {
     fn end_game(env: Env, session_id: u32, player1_won: bool);
 }
 
+/// Optional integration told about every death as it resolves; see `set_death_hook`.
+#[contractclient(name = "DeathHookClient")]
+pub trait DeathHook {
+    fn on_death(env: Env, session_id: u32, slot: u32, role: u32, cause: u32);
+}
+
 #[contract]
 pub struct MafiaDuelContract;
 
@@ -119,24 +679,275 @@ impl MafiaDuelContract {
         env.storage().instance().set(&DataKey::GameHubAddress, &game_hub);
     }
 
-    fn hub_client(env: &Env) -> GameHubClient {
+    fn hub_client(env: &Env) -> GameHubClient<'_> {
         let addr: Address = env.storage().instance().get(&DataKey::GameHubAddress).unwrap();
         GameHubClient::new(env, &addr)
     }
 
+    /// Games live in temporary storage unless archived, in which case persistent storage holds them.
+    fn load_game(env: &Env, session_id: u32) -> Option<Game> {
+        let key = DataKey::Game(session_id);
+        env.storage().temporary().get(&key).or_else(|| env.storage().persistent().get(&key))
+    }
+
+    fn load_finalized(env: &Env, session_id: u32) -> Option<FinalizedGame> {
+        env.storage().persistent().get(&DataKey::Finalized(session_id))
+    }
+
+    fn load_final_sync(env: &Env, session_id: u32) -> Option<FullSync> {
+        env.storage().persistent().get(&DataKey::FinalSync(session_id))
+    }
+
+    fn store_finalized(env: &Env, session_id: u32, fin: &FinalizedGame, sync: &FullSync) {
+        let (fin_key, sync_key) = (DataKey::Finalized(session_id), DataKey::FinalSync(session_id));
+        env.storage().persistent().set(&fin_key, fin);
+        env.storage().persistent().set(&sync_key, sync);
+        env.storage().persistent().extend_ttl(&fin_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        env.storage().persistent().extend_ttl(&sync_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    /// A session id is taken while its game exists, after it was finalized, while a match holds
+    /// it, or while it is reserved: matches and sessions share the hub's game ids, so neither may
+    /// reuse the other's.
+    fn session_taken(env: &Env, session_id: u32) -> bool {
+        Self::load_game(env, session_id).is_some()
+            || env.storage().persistent().has(&DataKey::Finalized(session_id))
+            || env.storage().persistent().has(&DataKey::Match(session_id))
+            || env.storage().temporary().has(&DataKey::Reserved(session_id))
+    }
+
+    /// Whether `creator` may open `session_id`: it is free, or reserved for them.
+    fn session_free_for(env: &Env, session_id: u32, creator: &Address) -> bool {
+        let holder: Option<Address> = env.storage().temporary().get(&DataKey::Reserved(session_id));
+        match holder {
+            Some(holder) => holder == *creator,
+            None => !Self::session_taken(env, session_id),
+        }
+    }
+
+    /// Take `session_id` for `creator`, using up their reservation of it if they hold one.
+    fn claim_session(env: &Env, session_id: u32, creator: &Address) -> Result<(), MafiaError> {
+        if !Self::session_free_for(env, session_id, creator) { return Err(MafiaError::SessionExists); }
+        env.storage().temporary().remove(&DataKey::Reserved(session_id));
+        Ok(())
+    }
+
+    fn active_count(env: &Env) -> u32 {
+        env.storage().persistent().get(&DataKey::ActiveCount).unwrap_or(0)
+    }
+
+    fn set_active_count(env: &Env, count: u32) {
+        env.storage().persistent().set(&DataKey::ActiveCount, &count);
+        env.storage().persistent().extend_ttl(&DataKey::ActiveCount, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    /// Add or drop `session_id` in the index of games between `begin_game` and game over. One
+    /// entry per game, so neither costs more as the index grows.
+    fn set_active(env: &Env, session_id: u32, active: bool) {
+        let pos: Option<u32> = env.storage().temporary().get(&DataKey::ActivePos(session_id));
+        match (pos, active) {
+            (None, true) => {
+                let count = Self::active_count(env);
+                Self::put_active(env, count, session_id);
+                Self::set_active_count(env, count + 1);
+            }
+            (Some(pos), false) => Self::drop_active(env, pos),
+            _ => {}
+        }
+    }
+
+    fn put_active(env: &Env, pos: u32, session_id: u32) {
+        let (at, back) = (DataKey::ActiveAt(pos), DataKey::ActivePos(session_id));
+        env.storage().temporary().set(&at, &session_id);
+        env.storage().temporary().set(&back, &pos);
+        env.storage().temporary().extend_ttl(&at, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        env.storage().temporary().extend_ttl(&back, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    /// Free index position `pos`, moving the last entry into it so positions stay dense.
+    fn drop_active(env: &Env, pos: u32) {
+        let tmp = env.storage().temporary();
+        let last = Self::active_count(env).saturating_sub(1);
+        if let Some(id) = tmp.get::<_, u32>(&DataKey::ActiveAt(pos)) { tmp.remove(&DataKey::ActivePos(id)); }
+        match tmp.get::<_, u32>(&DataKey::ActiveAt(last)) {
+            Some(moved) if pos != last => Self::put_active(env, pos, moved),
+            _ => tmp.remove(&DataKey::ActiveAt(pos)),
+        }
+        tmp.remove(&DataKey::ActiveAt(last));
+        Self::set_active_count(env, last);
+    }
+
+    /// Keep a running game's index entries alive as long as the game, re-adding them if they lapsed.
+    fn touch_active(env: &Env, session_id: u32) {
+        let tmp = env.storage().temporary();
+        let Some(pos) = tmp.get::<_, u32>(&DataKey::ActivePos(session_id)) else {
+            return Self::set_active(env, session_id, true);
+        };
+        tmp.extend_ttl(&DataKey::ActivePos(session_id), GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        if tmp.has(&DataKey::ActiveAt(pos)) { tmp.extend_ttl(&DataKey::ActiveAt(pos), GAME_TTL_LEDGERS, GAME_TTL_LEDGERS); }
+    }
+
+    /// Drop a game's full state and the sealed notes and private results hung off it.
+    fn remove_game(env: &Env, session_id: u32, game: &Game) {
+        Self::set_active(env, session_id, false);
+        env.storage().persistent().remove(&DataKey::Created(session_id));
+        let key = DataKey::Game(session_id);
+        let persistent = env.storage().persistent().has(&key);
+        for k in Self::side_keys(env, session_id, game, false).iter() {
+            if persistent { env.storage().persistent().remove(&k) } else { env.storage().temporary().remove(&k) }
+        }
+        env.storage().temporary().remove(&key);
+        env.storage().persistent().remove(&key);
+    }
+
+    /// The entries `game` may have hung off it, in its tier and sharing its TTL: the confidant's
+    /// intel, the doctor's feedback, each human's sealed note and watcher peek and, with
+    /// `transcript`, the reveal transcript. Only keys the game's rules and seats allow are listed,
+    /// to keep every call's footprint small.
+    fn side_keys(env: &Env, session_id: u32, game: &Game, transcript: bool) -> Vec<DataKey> {
+        let mut keys = Vec::new(env);
+        keys.push_back(DataKey::DoctorFeedback(session_id));
+        if Self::has_rule(game, RULE_CONFIDANT) { keys.push_back(DataKey::ConfidantIntel(session_id)); }
+        if transcript && Self::has_rule(game, RULE_TRANSCRIPT) { keys.push_back(DataKey::Transcript(session_id)); }
+        for i in 0..MAX_PLAYERS {
+            if game.slots.get(i).unwrap().addr.is_none() { continue; }
+            keys.push_back(DataKey::SealedNote(session_id, i));
+            if Self::has_rule(game, RULE_WATCHER) { keys.push_back(DataKey::Peek(session_id, i)); }
+        }
+        keys
+    }
+
+    /// Write an entry beside the game, in whichever tier the game is in.
+    fn put_side<V: IntoVal<Env, Val>>(env: &Env, session_id: u32, key: &DataKey, value: &V) {
+        if env.storage().persistent().has(&DataKey::Game(session_id)) {
+            env.storage().persistent().set(key, value);
+            env.storage().persistent().extend_ttl(key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        } else {
+            env.storage().temporary().set(key, value);
+            env.storage().temporary().extend_ttl(key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        }
+    }
+
+    fn get_side<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+        env.storage().temporary().get(key).or_else(|| env.storage().persistent().get(key))
+    }
+
+    fn remove_side(env: &Env, key: &DataKey) {
+        env.storage().temporary().remove(key);
+        env.storage().persistent().remove(key);
+    }
+
+    /// Carry an entry beside the game into (or back out of) persistent storage, if it exists.
+    fn move_side(env: &Env, key: &DataKey, to_persistent: bool) {
+        let (tmp, per) = (env.storage().temporary(), env.storage().persistent());
+        if to_persistent {
+            let Some(value) = tmp.get::<_, Val>(key) else { return };
+            tmp.remove(key);
+            per.set(key, &value);
+            per.extend_ttl(key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        } else {
+            let Some(value) = per.get::<_, Val>(key) else { return };
+            per.remove(key);
+            tmp.set(key, &value);
+            tmp.extend_ttl(key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        }
+    }
+
+    fn load(env: &Env, session_id: u32) -> Result<Game, MafiaError> {
+        Self::load_game(env, session_id).ok_or_else(|| Self::missing(env, session_id))
+    }
+
+    /// Why a session has no game: GameExpired if it was created and lapsed, GameNotFound otherwise.
+    fn missing(env: &Env, session_id: u32) -> MafiaError {
+        if env.storage().persistent().has(&DataKey::Created(session_id)) { MafiaError::GameExpired }
+        else { MafiaError::GameNotFound }
+    }
+
     fn store(env: &Env, session_id: u32, game: &Game) {
         let key = DataKey::Game(session_id);
-        env.storage().temporary().set(&key, game);
-        env.storage().temporary().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        let persistent = env.storage().persistent().has(&key);
+        if persistent {
+            env.storage().persistent().set(&key, game);
+            env.storage().persistent().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        } else {
+            env.storage().temporary().set(&key, game);
+            env.storage().temporary().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        }
+        // Entries beside the game lapse with it, not before.
+        for k in Self::side_keys(env, session_id, game, true).iter() {
+            if persistent && env.storage().persistent().has(&k) {
+                env.storage().persistent().extend_ttl(&k, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+            } else if !persistent && env.storage().temporary().has(&k) {
+                env.storage().temporary().extend_ttl(&k, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+            }
+        }
+        let created = DataKey::Created(session_id);
+        if env.storage().persistent().has(&created) {
+            env.storage().persistent().extend_ttl(&created, CREATED_TTL_LEDGERS, CREATED_TTL_LEDGERS);
+        }
+        if game.phase != PHASE_LOBBY && game.phase != PHASE_OVER { Self::touch_active(env, session_id); }
+    }
+
+    /// The transcript lives beside the game, in whichever tier the game is in.
+    fn append_transcript(env: &Env, session_id: u32, proof: RevealProof) {
+        let key = DataKey::Transcript(session_id);
+        let mut log: Vec<RevealProof> = Self::get_side(env, &key).unwrap_or(Vec::new(env));
+        log.push_back(proof);
+        Self::put_side(env, session_id, &key, &log);
+    }
+
+    fn require_creator_or_admin(env: &Env, game: &Game, caller: &Address) -> Result<(), MafiaError> {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *caller != game.creator && *caller != admin { return Err(MafiaError::NotCreator); }
+        Ok(())
     }
 
-    fn seed_prng(env: &Env, session_id: u32, day: u32, phase: u32) {
+    /// keccak256(session_be || day_be || phase_be), or keccak256(rng_seed || day_be || phase_be)
+    /// when the creator fixed a seed, so equal seeds replay identically across sessions.
+    fn prng_seed(env: &Env, game: &Game, session_id: u32, day: u32, phase: u32) -> BytesN<32> {
         let mut b = [0u8; 12];
         b[0..4].copy_from_slice(&session_id.to_be_bytes());
         b[4..8].copy_from_slice(&day.to_be_bytes());
         b[8..12].copy_from_slice(&phase.to_be_bytes());
-        let hash: BytesN<32> = env.crypto().keccak256(&Bytes::from_array(env, &b)).into();
-        env.prng().seed(hash.into());
+        let preimage = match &game.rng_seed {
+            Some(seed) => {
+                let mut p = Bytes::from_array(env, &seed.to_array());
+                p.extend_from_slice(&b[4..12]);
+                p
+            }
+            None => Bytes::from_array(env, &b),
+        };
+        env.crypto().keccak256(&preimage).into()
+    }
+
+    /// Fill in the AI's actions for the open phase, drawn exactly as resolution will draw them.
+    fn plan_ai(env: &Env, session_id: u32, game: &mut Game) {
+        Self::seed_prng(env, game, session_id, game.day, game.phase);
+        if game.phase == PHASE_NIGHT_REVEAL { Self::fill_ai_night(env, game); } else { Self::fill_ai_day(env, game); }
+    }
+
+    fn seed_prng(env: &Env, game: &Game, session_id: u32, day: u32, phase: u32) {
+        env.prng().seed(Self::prng_seed(env, game, session_id, day, phase).into());
+    }
+
+    /// The deal from `begin_game`'s seed, already in the PRNG: the seat-shuffle draws (under
+    /// RULE_SHUFFLE_SEATS) come first, then the Fisher-Yates role shuffle. Returns (swaps, roles),
+    /// where seat i was swapped with swaps[i] walking down from n - 1.
+    fn deal(env: &Env, game: &Game) -> ([u32; MAX_PLAYERS as usize], [u32; MAX_PLAYERS as usize]) {
+        let n = game.config.player_count;
+        let mut swaps = [0u32; MAX_PLAYERS as usize];
+        if Self::has_rule(game, RULE_SHUFFLE_SEATS) {
+            for i in (1..n).rev() {
+                swaps[i as usize] = env.prng().gen_range::<u64>(0..=(i as u64)) as u32;
+            }
+        }
+        let mut roles = Self::role_template(game, n);
+        for i in (1..n as usize).rev() {
+            let j = env.prng().gen_range::<u64>(0..=(i as u64)) as usize;
+            roles.swap(i, j);
+        }
+        (swaps, roles)
     }
 
     fn pick_random(env: &Env, list: &Vec<u32>) -> Option<u32> {
@@ -154,6 +965,17 @@ impl MafiaDuelContract {
         Self::pick_random(env, &filtered)
     }
 
+    /// Like pick_excluding, but `favored` counts as `weight` entries when it is on the list.
+    fn pick_weighted(env: &Env, list: &Vec<u32>, exclude: u32, favored: u32, weight: u32) -> Option<u32> {
+        if favored == exclude || !list.contains(favored) { return Self::pick_excluding(env, list, exclude); }
+        let mut rest = Vec::new(env);
+        for v in list.iter() {
+            if v != exclude && v != favored { rest.push_back(v); }
+        }
+        let roll = env.prng().gen_range::<u64>(0..(rest.len() + weight) as u64) as u32;
+        if roll < weight { Some(favored) } else { rest.get(roll - weight) }
+    }
+
     fn living_lists(env: &Env, game: &Game) -> (Vec<u32>, Vec<u32>) {
         let mut all  = Vec::new(env);
         let mut town = Vec::new(env);
@@ -167,226 +989,2038 @@ impl MafiaDuelContract {
         (all, town)
     }
 
-    fn compute_commitment(env: &Env, target: u32, nonce: u64) -> BytesN<32> {
-        let mut raw = [0u8; 12];
-        raw[0..4].copy_from_slice(&target.to_be_bytes());
-        raw[4..12].copy_from_slice(&nonce.to_be_bytes());
-        env.crypto().sha256(&Bytes::from_array(env, &raw)).into()
-    }
-
-    fn find_human_slot(game: &Game, player: &Address) -> Option<u32> {
-        for i in 0..game.slots.len() {
-            if let Some(ref a) = game.slots.get(i).unwrap().addr {
-                if a == player { return Some(i); }
-            }
+    fn team_of(role: u32) -> u32 {
+        match role {
+            ROLE_MAFIA         => TEAM_MAFIA,
+            ROLE_SERIAL_KILLER => TEAM_SERIAL_KILLER,
+            _                  => TEAM_TOWN,
         }
-        None
     }
 
-    fn all_alive_humans_submitted(game: &Game) -> bool {
+    /// Living (mafia, town, neutral) counts.
+    fn faction_counts(game: &Game) -> (u32, u32, u32) {
+        let (mut mafia, mut town, mut neutral) = (0u32, 0u32, 0u32);
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
-            if s.addr.is_some() && s.alive && !s.submitted { return false; }
+            if !s.alive { continue; }
+            match Self::team_of(s.role) {
+                TEAM_MAFIA => mafia += 1,
+                TEAM_TOWN  => town += 1,
+                _          => neutral += 1,
+            }
         }
-        true
+        (mafia, town, neutral)
     }
 
-    pub fn create_game(env: Env, session_id: u32, creator: Address, wager: i128) -> Result<(), MafiaError> {
-        creator.require_auth();
-        if env.storage().temporary().has(&DataKey::Game(session_id)) {
-            return Err(MafiaError::SessionExists);
-        }
-        let mut slots = Vec::new(&env);
-        slots.push_back(Slot { addr: Some(creator.clone()), role: 0, alive: true, action: None, submitted: false, commitment: None });
-        for _ in 1..MAX_PLAYERS {
-            slots.push_back(Slot { addr: None, role: 0, alive: true, action: None, submitted: false, commitment: None });
+    /// The team every living player is on, if they all share one; town once no one is left.
+    fn sole_faction(game: &Game) -> Option<u32> {
+        match Self::faction_counts(game) {
+            (0, _, 0) => Some(TEAM_TOWN),
+            (_, 0, 0) => Some(TEAM_MAFIA),
+            (0, 0, _) => Some(TEAM_SERIAL_KILLER),
+            _ => None,
         }
-        Self::store(&env, session_id, &Game {
-            creator, slots, human_count: 1, phase: PHASE_LOBBY, day: 0,
-            winner: None, last_killed: None, last_saved: false,
-            last_investigated: None, invest_is_mafia: false,
-            last_voted_out: None, wager,
-        });
+    }
+
+    /// Winner precedence, checked after every resolution:
+    /// 1. A faction left alone at the table wins; with mafia and serial killer both dead, town wins.
+    /// 2. A living serial killer facing at most one other player wins outright.
+    /// 3. Mafia win once they match everyone else alive, unless RULE_NEUTRAL_BLOCKS_PARITY is set
+    ///    and the serial killer still lives.
+    fn check_winner(game: &Game) -> Option<u32> {
+        if let Some(team) = Self::sole_faction(game) { return Some(team); }
+        let (mafia, town, neutral) = Self::faction_counts(game);
+        if neutral > 0 && mafia + town <= 1 { return Some(TEAM_SERIAL_KILLER); }
+        let blocked = neutral > 0 && Self::has_rule(game, RULE_NEUTRAL_BLOCKS_PARITY);
+        if mafia > 0 && mafia >= town + neutral && !blocked { return Some(TEAM_MAFIA); }
+        None
+    }
+
+    fn has_rule(game: &Game, rule: u32) -> bool {
+        game.config.rules & rule != 0
+    }
+
+    fn default_config() -> GameConfig {
+        GameConfig { rules: 0, grace_ledgers: 0, tie_breaker: TIE_FIRST_SLOT, day_eliminations: 1, player_count: MAX_PLAYERS, consolation_bps: 0,
+            ability_charges: 1, recharge_every: 0, discussion_ledgers: 0, practice: false,
+            mafia_tie_breaker: MAFIA_TIE_RANDOM, stake_timing: STAKE_NONE }
+    }
+
+    fn validate_config(config: &GameConfig) -> Result<(), MafiaError> {
+        if config.tie_breaker > TIE_SUSPECT { return Err(MafiaError::InvalidConfig); }
+        if config.mafia_tie_breaker > MAFIA_TIE_FIRST { return Err(MafiaError::InvalidConfig); }
+        if config.stake_timing > STAKE_ON_BEGIN { return Err(MafiaError::InvalidConfig); }
+        if config.player_count < MIN_PLAYERS || config.player_count > MAX_PLAYERS { return Err(MafiaError::InvalidConfig); }
+        if config.day_eliminations == 0 || config.day_eliminations >= config.player_count { return Err(MafiaError::InvalidConfig); }
+        if config.consolation_bps > BPS_DENOMINATOR { return Err(MafiaError::InvalidConfig); }
+        if config.ability_charges == 0 { return Err(MafiaError::InvalidConfig); }
         Ok(())
     }
 
-    pub fn join_game(env: Env, session_id: u32, player: Address) -> Result<(), MafiaError> {
-        player.require_auth();
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
-        if game.human_count >= MAX_PLAYERS { return Err(MafiaError::GameFull); }
-        for i in 0..game.slots.len() {
-            if let Some(ref a) = game.slots.get(i).unwrap().addr {
-                if *a == player { return Err(MafiaError::AlreadyJoined); }
-            }
+    /// Unshuffled roles for an `n`-seat table: a mafia per three seats, one doctor and one sheriff,
+    /// villagers for the rest (the first three may become vigilante, serial killer and confidant),
+    /// ROLE_NONE past `n`.
+    fn role_template(game: &Game, n: u32) -> [u32; MAX_PLAYERS as usize] {
+        let mut roles = [ROLE_NONE; MAX_PLAYERS as usize];
+        let mafia = n / 3;
+        for i in 0..n {
+            roles[i as usize] = if i < mafia { ROLE_MAFIA }
+                else if i == mafia { ROLE_DOCTOR }
+                else if i == mafia + 1 { ROLE_SHERIFF }
+                else { ROLE_VILLAGER };
         }
-        let slot_idx = game.human_count;
-        let mut s = game.slots.get(slot_idx).unwrap();
-        s.addr = Some(player);
-        game.slots.set(slot_idx, s);
-        game.human_count += 1;
-        Self::store(&env, session_id, &game);
-        Ok(())
+        let (vig, sk, conf) = (mafia + 2, mafia + 3, mafia + 4);
+        if vig < n && Self::has_rule(game, RULE_VIGILANTE) { roles[vig as usize] = ROLE_VIGILANTE; }
+        if sk < n && Self::has_rule(game, RULE_SERIAL_KILLER) { roles[sk as usize] = ROLE_SERIAL_KILLER; }
+        if conf < n && Self::has_rule(game, RULE_CONFIDANT) { roles[conf as usize] = ROLE_CONFIDANT; }
+        roles
     }
 
-    pub fn begin_game(env: Env, session_id: u32, caller: Address) -> Result<(), MafiaError> {
-        caller.require_auth();
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.creator != caller { return Err(MafiaError::NotCreator); }
-        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
-        Self::seed_prng(&env, session_id, 0, 0);
-        let mut roles = ROLE_TEMPLATE;
-        for i in (1..8usize).rev() {
-            let j = env.prng().gen_range::<u64>(0..=(i as u64)) as usize;
-            roles.swap(i, j);
+    /// Start the clock on every seat owing an action in the phase that just opened.
+    fn open_phase(env: &Env, game: &mut Game) {
+        let mut now = env.ledger().sequence();
+        game.deadline_extension = 0;
+        if game.phase == PHASE_DAY {
+            // With no human left to talk, the bots vote at once so auto-advance can keep going.
+            if Self::humans_alive(game) { now = now.saturating_add(game.config.discussion_ledgers); }
+            game.votes_open_at = now;
         }
+        let mask = Self::has_rule(game, RULE_MASK_BOTS)
+            && (game.phase == PHASE_NIGHT_COMMIT || game.phase == PHASE_NIGHT_REVEAL);
         for i in 0..MAX_PLAYERS {
             let mut s = game.slots.get(i).unwrap();
-            s.role = roles[i as usize];
+            s.silent_since = now;
+            if mask && s.addr.is_none() && s.alive {
+                // Decoy only: bot actions are still drawn from the seeded PRNG at resolve time.
+                if game.phase == PHASE_NIGHT_COMMIT { s.commitment = Some(env.prng().gen::<BytesN<32>>()); }
+                s.submitted = true;
+            }
             game.slots.set(i, s);
         }
-        game.phase = PHASE_NIGHT_COMMIT;
-        game.day   = 1;
-        Self::hub_client(&env).start_game(
-            &env.current_contract_address(), &session_id,
-            &game.creator, &game.creator, &game.wager, &game.wager,
-        );
-        Self::store(&env, session_id, &game);
-        Ok(())
     }
 
-    /// ZK Step 1 (hiding): store commitment = sha256(target_be || nonce_be).
-    /// Auto-advances to PHASE_NIGHT_REVEAL once all alive humans commit.
-    pub fn submit_commitment(
-        env: Env,
-        session_id: u32,
-        player: Address,
-        commitment: BytesN<32>,
-    ) -> Result<(), MafiaError> {
-        player.require_auth();
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.phase != PHASE_NIGHT_COMMIT { return Err(MafiaError::WrongPhase); }
-        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
-        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
-        let mut s = game.slots.get(idx).unwrap();
-        if !s.alive { return Err(MafiaError::NotAlive); }
+    fn begin_reveal(env: &Env, game: &mut Game) {
+        game.phase = PHASE_NIGHT_REVEAL;
+        for i in 0..MAX_PLAYERS {
+            let mut slot = game.slots.get(i).unwrap();
+            if slot.addr.is_some() { slot.submitted = false; }
+            game.slots.set(i, slot);
+        }
+        Self::open_phase(env, game);
+    }
+
+    /// With a grace period configured, true once every silent living human has used it up.
+    /// Humans who never committed are already AI-driven by the reveal phase and don't block it.
+    fn grace_expired(env: &Env, game: &Game) -> bool {
+        let now = env.ledger().sequence();
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.addr.is_none() || !s.alive || s.submitted || s.afk { continue; }
+            if game.phase == PHASE_NIGHT_REVEAL && s.commitment.is_none() { continue; }
+            if now < Self::slot_deadline(game, &s) { return false; }
+        }
+        true
+    }
+
+    fn slot_deadline(game: &Game, s: &Slot) -> u32 {
+        s.silent_since.saturating_add(game.config.grace_ledgers).saturating_add(game.deadline_extension)
+    }
+
+    /// Ledger at which the current phase's grace runs out, if this game uses one.
+    fn phase_deadline(game: &Game) -> Option<u32> {
+        if game.config.grace_ledgers == 0 { return None; }
+        if game.phase != PHASE_NIGHT_COMMIT && game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY { return None; }
+        let mut deadline = 0;
+        for s in game.slots.iter() { deadline = deadline.max(Self::slot_deadline(game, &s)); }
+        Some(deadline)
+    }
+
+    /// A lobby no one has taken a seat in for ABANDON_LEDGERS; it may never begin, so its
+    /// escrowed stakes would otherwise only come back through `cancel_game`.
+    fn lobby_idle(env: &Env, game: &Game) -> bool {
+        let last_seated = game.slots.iter().map(|s| s.silent_since).max().unwrap_or(0);
+        game.phase == PHASE_LOBBY && env.ledger().sequence() >= last_seated.saturating_add(ABANDON_LEDGERS)
+    }
+
+    /// AI plays a seat if it is a bot, or a human who stayed silent past the grace period.
+    /// A human who committed but never revealed always passes: handing them to the AI would let
+    /// a withheld reveal buy a fresh random action, and every role is forced to pass alike.
+    fn ai_plays(game: &Game, s: &Slot) -> bool {
+        if !s.alive { return false; }
+        // Bots may carry masking `submitted` flags (RULE_MASK_BOTS) but never a real action.
+        if s.addr.is_none() { return true; }
+        if s.submitted { return false; }
+        s.afk || (game.config.grace_ledgers > 0 && s.commitment.is_none())
+    }
+
+    fn kill(game: &mut Game, slot: u32, cause: u32) {
+        let mut ds = game.slots.get(slot).unwrap();
+        ds.alive = false;
+        let reveal_rule = if cause == DEATH_LYNCH { RULE_REVEAL_ON_LYNCH } else { RULE_REVEAL_ON_NIGHTKILL };
+        let revealed_role = if Self::has_rule(game, reveal_rule) { Some(ds.role) } else { None };
+        game.slots.set(slot, ds);
+        game.graveyard.push_back(Grave { slot, day: game.day, cause, last_words: None, revealed_role });
+    }
+
+    /// Last words are accepted only during the phase that opened right after the death.
+    fn in_last_words_window(day: u32, phase: u32, grave: &Grave) -> bool {
+        if grave.cause != DEATH_LYNCH {
+            day == grave.day && (phase == PHASE_DAY || phase == PHASE_OVER)
+        } else {
+            day == grave.day + 1 && (phase == PHASE_NIGHT_COMMIT || phase == PHASE_OVER)
+        }
+    }
+
+    /// Attach `words` to `slot`'s grave, if its window is open at (`day`, `phase`).
+    fn write_last_words(graveyard: &mut Vec<Grave>, slot: u32, day: u32, phase: u32, words: Bytes) -> Result<(), MafiaError> {
+        for g in 0..graveyard.len() {
+            let mut grave = graveyard.get(g).unwrap();
+            if grave.slot != slot { continue; }
+            if !Self::in_last_words_window(day, phase, &grave) { return Err(MafiaError::WrongPhase); }
+            if grave.last_words.is_some() { return Err(MafiaError::AlreadyActed); }
+            grave.last_words = Some(words);
+            graveyard.set(g, grave);
+            return Ok(());
+        }
+        Err(MafiaError::NotInGame)
+    }
+
+    fn load_stats(env: &Env, player: &Address) -> PlayerStats {
+        env.storage().persistent().get(&DataKey::Stats(player.clone())).unwrap_or(PlayerStats {
+            games_played: 0, games_won: 0, mafia_wins: 0, town_wins: 0, games_survived: 0,
+            roles_played: Vec::new(env),
+        })
+    }
+
+    /// Fold a finished game into each human's lifetime stats. Only a decided winner counts as a win.
+    fn record_stats(env: &Env, game: &Game) {
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            let Some(addr) = s.addr.clone() else { continue };
+            let mut st = Self::load_stats(env, &addr);
+            st.games_played = st.games_played.saturating_add(1);
+            if s.alive { st.games_survived = st.games_survived.saturating_add(1); }
+            let team = Self::team_of(s.role);
+            if game.winner == Some(team) {
+                st.games_won = st.games_won.saturating_add(1);
+                if team == TEAM_MAFIA { st.mafia_wins = st.mafia_wins.saturating_add(1); }
+                else if team == TEAM_TOWN { st.town_wins = st.town_wins.saturating_add(1); }
+            }
+            while st.roles_played.len() < ROLE_COUNT { st.roles_played.push_back(0); }
+            if s.role < st.roles_played.len() {
+                st.roles_played.set(s.role, st.roles_played.get(s.role).unwrap().saturating_add(1));
+            }
+            let key = DataKey::Stats(addr);
+            env.storage().persistent().set(&key, &st);
+            env.storage().persistent().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        }
+    }
+
+    fn load_role_stats(env: &Env) -> RoleStats {
+        let mut zeros = Vec::new(env);
+        for _ in 0..ROLE_COUNT { zeros.push_back(0u32); }
+        env.storage().persistent().get(&DataKey::RoleStats).unwrap_or(RoleStats { games: zeros.clone(), wins: zeros })
+    }
+
+    fn record_role_stats(env: &Env, game: &Game) {
+        let mut st = Self::load_role_stats(env);
+        for role in 0..ROLE_COUNT {
+            if !game.slots.iter().any(|s| s.role == role) { continue; }
+            st.games.set(role, st.games.get(role).unwrap().saturating_add(1));
+            if game.winner == Some(Self::team_of(role)) {
+                st.wins.set(role, st.wins.get(role).unwrap().saturating_add(1));
+            }
+        }
+        env.storage().persistent().set(&DataKey::RoleStats, &st);
+        env.storage().persistent().extend_ttl(&DataKey::RoleStats, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    fn load_balance(env: &Env) -> BalanceStats {
+        env.storage().persistent().get(&DataKey::BalanceStats).unwrap_or(BalanceStats {
+            kills_attempted: 0, saves_succeeded: 0, investigations: 0, lynches: 0,
+        })
+    }
+
+    fn store_balance(env: &Env, stats: &BalanceStats) {
+        env.storage().persistent().set(&DataKey::BalanceStats, stats);
+        env.storage().persistent().extend_ttl(&DataKey::BalanceStats, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    fn finish_game(env: &Env, session_id: u32, game: &mut Game) -> Result<(), MafiaError> {
+        Self::set_active(env, session_id, false);
+        Self::settle_stakes(env, game)?;
+        Self::record_role_stats(env, game);
+        if let Some(match_id) = game.match_id {
+            Self::record_stats(env, game);
+            return Self::record_round(env, match_id, game);
+        }
+        let w = game.winner.unwrap();
+        let (player1, _) = Self::hub_players(game);
+        let role = game.slots.get(Self::find_human_slot(game, &player1).unwrap()).unwrap().role;
+        Self::hub_client(env).end_game(&session_id, &(Self::team_of(role) == w));
+        Self::record_stats(env, game);
+        Ok(())
+    }
+
+    fn load_match(env: &Env, match_id: u32) -> Option<Match> {
+        env.storage().persistent().get(&DataKey::Match(match_id))
+    }
+
+    fn store_match(env: &Env, match_id: u32, m: &Match) {
+        let key = DataKey::Match(match_id);
+        env.storage().persistent().set(&key, m);
+        env.storage().persistent().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    }
+
+    /// Open the next round's lobby with every match player seated in order.
+    fn new_round(env: &Env, match_id: u32, players: &Vec<Address>, wager: i128, config: GameConfig) -> Result<u32, MafiaError> {
+        let session_id = Self::next_session_id(env);
+        let mut game = Self::new_game(env, session_id, players.get(0).unwrap(), wager)?;
+        game.config   = config;
+        game.match_id = Some(match_id);
+        for p in players.iter().skip(1) { Self::seat_player(env, &mut game, p)?; }
+        Self::store(env, session_id, &game);
+        Ok(session_id)
+    }
+
+    /// Credit a finished round. Once a player holds a majority of `rounds`, or every round has
+    /// been played, the match ends and the hub is told whether player 1 won more rounds than
+    /// player 2; otherwise the next round's lobby opens with the same wager and config. Fails, and
+    /// so holds the round open, if that lobby can't be opened (say the wager limits changed).
+    fn record_round(env: &Env, match_id: u32, game: &Game) -> Result<(), MafiaError> {
+        let mut m = Self::load_match(env, match_id).ok_or(MafiaError::GameNotFound)?;
+        for (i, p) in m.players.iter().enumerate() {
+            let idx = Self::find_human_slot(game, &p).ok_or(MafiaError::NotInGame)?;
+            let role = game.slots.get(idx).unwrap().role;
+            if game.winner == Some(Self::team_of(role)) {
+                m.wins.set(i as u32, m.wins.get(i as u32).unwrap().saturating_add(1));
+            }
+        }
+        let majority = m.rounds / 2 + 1;
+        if m.wins.iter().any(|w| w >= majority) || m.sessions.len() >= m.rounds {
+            m.over = true;
+            let player1_won = m.wins.get(0).unwrap() > m.wins.get(1).unwrap();
+            Self::hub_client(env).end_game(&match_id, &player1_won);
+        } else {
+            let next = Self::new_round(env, match_id, &m.players, game.wager, game.config.clone())?;
+            m.sessions.push_back(next);
+        }
+        Self::store_match(env, match_id, &m);
+        Ok(())
+    }
+
+    /// The 12 bytes a commitment hashes: target_be (4) || nonce_be (8).
+    fn commitment_bytes(env: &Env, target: u32, nonce: u64) -> Bytes {
+        let mut raw = [0u8; 12];
+        raw[0..4].copy_from_slice(&target.to_be_bytes());
+        raw[4..12].copy_from_slice(&nonce.to_be_bytes());
+        Bytes::from_array(env, &raw)
+    }
+
+    /// No sha256 output is realistically all zeros; such a commitment is a client hashing bug
+    /// and could never be revealed.
+    fn check_commitment(commitment: &BytesN<32>) -> Result<(), MafiaError> {
+        if commitment.to_array() == [0u8; 32] { return Err(MafiaError::InvalidCommitment); }
+        Ok(())
+    }
+
+    fn compute_commitment(env: &Env, target: u32, nonce: u64) -> BytesN<32> {
+        env.crypto().sha256(&Self::commitment_bytes(env, target, nonce)).into()
+    }
+
+    /// Merkle leaf for one action component: sha256(component_be || target_be || nonce_be), so a
+    /// leaf only opens at the position it was built for.
+    fn merkle_leaf(env: &Env, component: u32, target: u32, nonce: u64) -> BytesN<32> {
+        let mut buf = Bytes::from_array(env, &component.to_be_bytes());
+        buf.append(&Self::commitment_bytes(env, target, nonce));
+        env.crypto().sha256(&buf).into()
+    }
+
+    /// Fold a leaf up a Merkle path: bit `d` of `index` says whether the node at depth `d` is a
+    /// right child. Parents are sha256(left || right). An empty path returns the leaf itself.
+    fn merkle_root(env: &Env, leaf: BytesN<32>, path: &Vec<BytesN<32>>, index: u32) -> BytesN<32> {
+        let mut node = leaf;
+        for (d, sibling) in path.iter().enumerate() {
+            let mut buf = Bytes::new(env);
+            if (index >> d) & 1 == 1 {
+                buf.append(&sibling.into());
+                buf.append(&node.into());
+            } else {
+                buf.append(&node.into());
+                buf.append(&sibling.into());
+            }
+            node = env.crypto().sha256(&buf).into();
+        }
+        node
+    }
+
+    /// Rules a revealed night target must satisfy; PASS_TARGET maps to no action.
+    fn check_night_target(game: &Game, idx: u32, s: &Slot, target: u32) -> Result<Option<u32>, MafiaError> {
+        if target == PASS_TARGET {
+            if s.role == ROLE_MAFIA && Self::has_rule(game, RULE_MAFIA_MUST_KILL) { return Err(MafiaError::InvalidTarget); }
+            return Ok(None);
+        }
+        if s.role != ROLE_MAFIA && Self::has_rule(game, RULE_NO_POWERS) { return Err(MafiaError::InvalidTarget); }
+        if target >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
+        let ts = game.slots.get(target).unwrap();
+        if !ts.alive { return Err(MafiaError::InvalidTarget); }
+        let aims_outward = matches!(s.role, ROLE_MAFIA | ROLE_SHERIFF | ROLE_VIGILANTE | ROLE_SERIAL_KILLER);
+        if target == idx && aims_outward {
+            return Err(MafiaError::InvalidTarget);
+        }
+        if s.role == ROLE_VILLAGER && Self::has_rule(game, RULE_WATCHER) && s.ability_charges == 0 {
+            return Err(MafiaError::AbilityUsed);
+        }
+        // Mafia may not kill a teammate unless the game opts into team kills (or hides the team).
+        if s.role == ROLE_MAFIA && Self::team_of(ts.role) == TEAM_MAFIA && !Self::has_rule(game, RULE_TEAM_KILL | RULE_MAFIA_ANONYMOUS) {
+            return Err(MafiaError::InvalidTarget);
+        }
+        Ok(Some(target))
+    }
+
+    fn find_human_slot(game: &Game, player: &Address) -> Option<u32> {
+        for i in 0..game.slots.len() {
+            if let Some(ref a) = game.slots.get(i).unwrap().addr {
+                if a == player { return Some(i); }
+            }
+        }
+        None
+    }
+
+    /// The hub tracks a two-player match: the first two seated humans, or the creator twice
+    /// when every other seat is a bot.
+    fn hub_players(game: &Game) -> (Address, Address) {
+        let mut humans = game.slots.iter().filter_map(|s| s.addr);
+        let player1 = humans.next().unwrap_or(game.creator.clone());
+        let player2 = humans.next().unwrap_or(player1.clone());
+        (player1, player2)
+    }
+
+    /// Refuse game actions while paused. A pause that has outrun MAX_PAUSE_LEDGERS is lifted here.
+    fn ensure_unpaused(env: &Env, game: &mut Game) -> Result<(), MafiaError> {
+        if !game.paused { return Ok(()); }
+        if Self::pause_holds(env, game) { return Err(MafiaError::GamePaused); }
+        Self::unpause(env, game);
+        Ok(())
+    }
+
+    /// Whether `submit_action` would take a day vote now: the morning is announced, no pause
+    /// holds and the discussion window is over.
+    fn votes_open(env: &Env, game: &Game) -> bool {
+        game.phase == PHASE_DAY && !game.pending_announcement && !Self::pause_holds(env, game)
+            && env.ledger().sequence() >= game.votes_open_at
+    }
+
+    /// True while a pause is set and still within its MAX_PAUSE_LEDGERS budget.
+    fn pause_holds(env: &Env, game: &Game) -> bool {
+        let elapsed = env.ledger().sequence().saturating_sub(game.paused_at);
+        game.paused && game.pause_used.saturating_add(elapsed) < MAX_PAUSE_LEDGERS
+    }
+
+    /// Clear the pause and push every grace clock back by the time spent paused. Returns that time.
+    fn unpause(env: &Env, game: &mut Game) -> u32 {
+        let elapsed = env.ledger().sequence().saturating_sub(game.paused_at)
+            .min(MAX_PAUSE_LEDGERS - game.pause_used);
+        game.paused      = false;
+        game.pause_used += elapsed;
+        if game.votes_open_at > game.paused_at { game.votes_open_at = game.votes_open_at.saturating_add(elapsed); }
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            s.silent_since = s.silent_since.saturating_add(elapsed);
+            game.slots.set(i, s);
+        }
+        elapsed
+    }
+
+    /// Lift the morning announcement's hold; the death hook hears of the night's deaths only now.
+    fn publish_night(env: &Env, session_id: u32, game: &mut Game) {
+        let held = game.graveyard.iter().position(|g| Self::unannounced(game, &g)).map(|i| i as u32);
+        game.pending_announcement = false;
+        game.last_killed = game.pending_killed.take();
+        game.last_saved  = core::mem::replace(&mut game.pending_saved, false);
+        Self::log_night(game);
+        if let Some(from) = held { Self::notify_deaths(env, session_id, game, from); }
+    }
+
+    /// Refill every seat's ability charges on the nights `recharge_every` schedules: night
+    /// 1 + recharge_every, 1 + 2 * recharge_every, and so on.
+    fn recharge(game: &mut Game) {
+        let every = game.config.recharge_every;
+        if every == 0 || !(game.day - 1).is_multiple_of(every) { return; }
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            s.ability_charges = game.config.ability_charges;
+            game.slots.set(i, s);
+        }
+    }
+
+    /// Append tonight's record once the night's outcome is public.
+    fn log_night(game: &mut Game) {
+        let deaths = game.graveyard.iter().filter(|g| g.day == game.day && g.cause != DEATH_LYNCH).count() as u32;
+        game.night_log.push_back(NightRecord { day: game.day, deaths });
+    }
+
+    fn humans_alive(game: &Game) -> bool {
+        game.slots.iter().any(|s| s.addr.is_some() && s.alive)
+    }
+
+    fn hub_points(game: &Game, player: &Address) -> i128 {
+        if !Self::has_rule(game, RULE_ROLE_POINTS) { return game.wager; }
+        let role = game.slots.get(Self::find_human_slot(game, player).unwrap()).unwrap().role;
+        let pct = ROLE_POINT_PCT[role as usize];
+        // Divide first so a wager near the pot limit can't overflow; the split keeps it exact.
+        game.wager / 100 * pct + game.wager % 100 * pct / 100
+    }
+
+    fn all_alive_humans_submitted(game: &Game) -> bool {
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.addr.is_some() && s.alive && !s.submitted && !s.afk { return false; }
+        }
+        true
+    }
+
+    /// Count a missed phase for every living human the phase is resolving without; anyone who
+    /// acted starts over. Runs before the phase's actions are cleared.
+    fn track_missed(game: &mut Game) {
+        let botify = Self::has_rule(game, RULE_AFK_BOTIFY);
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            if s.addr.is_none() || !s.alive { continue; }
+            if s.submitted {
+                s.missed_phases = 0;
+                s.afk = false;
+            } else if game.phase != PHASE_NIGHT_REVEAL || s.commitment.is_some() {
+                s.missed_phases = s.missed_phases.saturating_add(1);
+                if botify && s.missed_phases >= AFK_PHASE_LIMIT { s.afk = true; }
+            } else {
+                continue;
+            }
+            game.slots.set(i, s);
+        }
+    }
+
+    fn new_slot(addr: Option<Address>) -> Slot {
+        Slot {
+            addr, role: 0, alive: true, action: None, submitted: false, commitment: None,
+            silent_since: 0, ability_charges: 0, bot_name: None,
+            locked: false, missed_phases: 0, afk: false, staked: false,
+        }
+    }
+
+    /// Take the next counter value no game uses yet.
+    fn next_session_id(env: &Env) -> u32 {
+        let mut id: u32 = env.storage().instance().get(&DataKey::NextSession).unwrap_or(0);
+        while Self::session_taken(env, id) { id = id.wrapping_add(1); }
+        env.storage().instance().set(&DataKey::NextSession, &id.wrapping_add(1));
+        id
+    }
+
+    /// Whether a creator may begin with every other seat a bot. Admin-set; on by default.
+    fn single_player_allowed(env: &Env) -> bool {
+        env.storage().instance().get(&DataKey::SinglePlayerAllowed).unwrap_or(true)
+    }
+
+    /// Move `amount` of the fee token from `payer` to the treasury. Free until the admin sets both
+    /// fees and a treasury; InsufficientFee if `payer` can't cover it.
+    fn charge_fee(env: &Env, payer: &Address, amount: fn(&Fees) -> i128) -> Result<(), MafiaError> {
+        let Some(fees) = env.storage().instance().get::<_, Fees>(&DataKey::Fees) else { return Ok(()) };
+        let Some(treasury) = env.storage().instance().get::<_, Address>(&DataKey::Treasury) else { return Ok(()) };
+        let amount = amount(&fees);
+        if amount == 0 { return Ok(()); }
+        let token = token::Client::new(env, &fees.token);
+        if token.balance(payer) < amount { return Err(MafiaError::InsufficientFee); }
+        token.transfer(payer, &treasury, &amount);
+        Ok(())
+    }
+
+    /// Escrow needs the admin's fee token to hold stakes in.
+    fn validate_stake(env: &Env, config: &GameConfig) -> Result<(), MafiaError> {
+        if config.stake_timing != STAKE_NONE && !env.storage().instance().has(&DataKey::Fees) {
+            return Err(MafiaError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Move the wager of the human at `idx` into escrow, unless it is already there. With
+    /// `from_allowance` it is pulled through the allowance they granted this contract, so they
+    /// needn't sign; otherwise they must have authorized the call.
+    fn take_stake(env: &Env, game: &mut Game, idx: u32, from_allowance: bool) -> Result<(), MafiaError> {
+        let mut s = game.slots.get(idx).unwrap();
+        if s.staked || game.wager == 0 { return Ok(()); }
+        let token_addr = match &game.stake_token {
+            Some(t) => t.clone(),
+            None => env.storage().instance().get::<_, Fees>(&DataKey::Fees).ok_or(MafiaError::InvalidConfig)?.token,
+        };
+        let token = token::Client::new(env, &token_addr);
+        let (player, escrow) = (s.addr.clone().unwrap(), env.current_contract_address());
+        if token.balance(&player) < game.wager { return Err(MafiaError::InsufficientStake); }
+        if from_allowance {
+            if token.allowance(&player, &escrow) < game.wager { return Err(MafiaError::InsufficientStake); }
+            token.transfer_from(&escrow, &player, &escrow, &game.wager);
+        } else {
+            token.transfer(&player, &escrow, &game.wager);
+        }
+        s.staked = true;
+        game.slots.set(idx, s);
+        game.stake_token = Some(token_addr);
+        Ok(())
+    }
+
+    /// Under STAKE_ON_JOIN, escrow the stake of the human who just took seat `idx`.
+    fn stake_on_join(env: &Env, game: &mut Game, idx: u32) -> Result<(), MafiaError> {
+        if game.config.stake_timing != STAKE_ON_JOIN { return Ok(()); }
+        Self::take_stake(env, game, idx, false)
+    }
+
+    /// Hand back the escrowed stake of the seat at `idx`, if it holds one.
+    fn refund_stake(env: &Env, game: &mut Game, idx: u32) {
+        let mut s = game.slots.get(idx).unwrap();
+        if !s.staked { return; }
+        let token = token::Client::new(env, game.stake_token.as_ref().unwrap());
+        token.transfer(&env.current_contract_address(), s.addr.as_ref().unwrap(), &game.wager);
+        s.staked = false;
+        game.slots.set(idx, s);
+    }
+
+    /// Pay the escrowed pot out as `get_payouts` splits it.
+    fn settle_stakes(env: &Env, game: &mut Game) -> Result<(), MafiaError> {
+        let Some(token_addr) = game.stake_token.clone() else { return Ok(()) };
+        let token = token::Client::new(env, &token_addr);
+        for (addr, amount) in Self::payouts(env, game)?.iter() {
+            if amount > 0 { token.transfer(&env.current_contract_address(), &addr, &amount); }
+        }
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            s.staked = false;
+            game.slots.set(i, s);
+        }
+        Ok(())
+    }
+
+    /// Admin-set (min, max) wager; never below zero.
+    fn wager_limits(env: &Env) -> (i128, i128) {
+        env.storage().instance().get(&DataKey::WagerLimits).unwrap_or((0, i128::MAX))
+    }
+
+    fn new_game(env: &Env, session_id: u32, creator: Address, wager: i128) -> Result<Game, MafiaError> {
+        Self::claim_session(env, session_id, &creator)?;
+        let (min_wager, max_wager) = Self::wager_limits(env);
+        if wager < min_wager || wager > max_wager { return Err(MafiaError::InvalidWager); }
+        // A full table's pot must fit in an i128.
+        if wager.checked_mul(MAX_PLAYERS as i128).is_none() { return Err(MafiaError::Overflow); }
+        let created = DataKey::Created(session_id);
+        env.storage().persistent().set(&created, &());
+        env.storage().persistent().extend_ttl(&created, CREATED_TTL_LEDGERS, CREATED_TTL_LEDGERS);
+        let mut slots = Vec::new(env);
+        let mut first = Self::new_slot(Some(creator.clone()));
+        first.silent_since = env.ledger().sequence();
+        slots.push_back(first);
+        for _ in 1..MAX_PLAYERS {
+            slots.push_back(Self::new_slot(None));
+        }
+        Ok(Game {
+            creator, slots, human_count: 1, phase: PHASE_LOBBY, day: 0,
+            winner: None, last_killed: None, last_saved: false,
+            last_investigated: None, invest_is_mafia: false,
+            last_voted_out: Vec::new(env), wager,
+            config: Self::default_config(),
+            graveyard: Vec::new(env),
+            claims: Vec::new(env),
+            runoff_candidates: Vec::new(env), runoff_round: 0,
+            deadline_extension: 0,
+            pending_announcement: false, pending_killed: None, pending_saved: false,
+            paused: false, paused_at: 0, pause_used: 0,
+            observers: Vec::new(env),
+            last_tally: Vec::new(env),
+            night_log: Vec::new(env),
+            night_reports: Vec::new(env),
+            rng_seed: None, match_id: None, doctor_exposed: None,
+            votes_open_at: 0,
+            accused: Vec::new(env), last_kill_source: None, stake_token: None,
+        })
+    }
+
+    /// Seat `player` in the next free slot of a lobby. In the lobby `silent_since` is the ledger
+    /// the seat was taken.
+    fn seat_player(env: &Env, game: &mut Game, player: Address) -> Result<(), MafiaError> {
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.human_count >= game.config.player_count { return Err(MafiaError::GameFull); }
+        if Self::find_human_slot(game, &player).is_some() { return Err(MafiaError::AlreadyJoined); }
+        if game.observers.contains(&player) { return Err(MafiaError::AlreadyJoined); }
+        let slot_idx = game.human_count;
+        let mut s = game.slots.get(slot_idx).unwrap();
+        s.addr         = Some(player);
+        s.bot_name     = None;
+        s.silent_since = env.ledger().sequence();
+        game.slots.set(slot_idx, s);
+        game.human_count += 1;
+        Self::assert_unique_addresses(game);
+        Ok(())
+    }
+
+    /// Invariant: no address holds two seats, or `find_human_slot` would silently pick the first.
+    fn assert_unique_addresses(game: &Game) {
+        for i in 0..MAX_PLAYERS {
+            let Some(a) = game.slots.get(i).unwrap().addr else { continue };
+            for j in (i + 1)..MAX_PLAYERS {
+                if game.slots.get(j).unwrap().addr.as_ref() == Some(&a) {
+                    panic!("Address seated twice in one game");
+                }
+            }
+        }
+    }
+
+    pub fn create_game(env: Env, session_id: u32, creator: Address, wager: i128) -> Result<(), MafiaError> {
+        creator.require_auth();
+        Self::charge_fee(&env, &creator, |f| f.create_fee)?;
+        let game = Self::new_game(&env, session_id, creator, wager)?;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Hand out a session_id no game uses yet, for the caller to pass to `create_game`.
+    /// Ids come from a counter, so no two reservations return the same one. The id is held for
+    /// `creator` alone until they create with it, or for GAME_TTL_LEDGERS if they never do.
+    pub fn reserve_session(env: Env, creator: Address) -> u32 {
+        creator.require_auth();
+        let id = Self::next_session_id(&env);
+        let key = DataKey::Reserved(id);
+        env.storage().temporary().set(&key, &creator);
+        env.storage().temporary().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        id
+    }
+
+    /// Best-of-`rounds` series between `players` (2..=MAX_PLAYERS, all signing). Opens round
+    /// one's lobby, creator `players[0]`, and returns its session_id; the creator may configure
+    /// it before `begin_game`, and later rounds inherit that config. The hub sees one game under
+    /// `match_id` between the first two players, settled when the match is. `match_id` comes from
+    /// the same pool as session ids, so it must not name an existing session either.
+    pub fn create_match(env: Env, match_id: u32, players: Vec<Address>, rounds: u32, wager: i128) -> Result<u32, MafiaError> {
+        for p in players.iter() { p.require_auth(); }
+        if players.len() < 2 || players.len() > MAX_PLAYERS { return Err(MafiaError::InvalidConfig); }
+        if rounds == 0 || rounds > MAX_MATCH_ROUNDS { return Err(MafiaError::InvalidConfig); }
+        Self::claim_session(&env, match_id, &players.get(0).unwrap())?;
+        let mut wins = Vec::new(&env);
+        for _ in 0..players.len() { wins.push_back(0); }
+        let mut m = Match { players, rounds, wins, sessions: Vec::new(&env), over: false };
+        // Hold `match_id` first so round one's session can't be handed the same id.
+        Self::store_match(&env, match_id, &m);
+        let session_id = Self::new_round(&env, match_id, &m.players, wager, Self::default_config())?;
+        let (player1, player2) = (m.players.get(0).unwrap(), m.players.get(1).unwrap());
+        Self::hub_client(&env).start_game(
+            &env.current_contract_address(), &match_id,
+            &player1, &player2, &wager, &wager,
+        );
+        m.sessions.push_back(session_id);
+        Self::store_match(&env, match_id, &m);
+        Ok(session_id)
+    }
+
+    pub fn get_match(env: Env, match_id: u32) -> Option<Match> {
+        Self::load_match(&env, match_id)
+    }
+
+    /// `create_game` with the full ruleset set up front, as `configure_game` would.
+    pub fn create_game_with_config(
+        env: Env,
+        session_id: u32,
+        creator: Address,
+        wager: i128,
+        config: GameConfig,
+    ) -> Result<(), MafiaError> {
+        creator.require_auth();
+        Self::validate_config(&config)?;
+        Self::validate_stake(&env, &config)?;
+        Self::charge_fee(&env, &creator, |f| f.create_fee)?;
+        let mut game = Self::new_game(&env, session_id, creator, wager)?;
+        game.config = config;
+        Self::stake_on_join(&env, &mut game, 0)?;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Quick-match: create the lobby with `config` and seat `second_player` in slot 1 in one
+    /// transaction. Under STAKE_ON_JOIN both stakes go into escrow; if either can't, nothing is created.
+    pub fn create_and_join(
+        env: Env,
+        session_id: u32,
+        creator: Address,
+        second_player: Address,
+        wager: i128,
+        config: GameConfig,
+    ) -> Result<u32, MafiaError> {
+        if creator == second_player { return Err(MafiaError::AlreadyJoined); }
+        creator.require_auth();
+        second_player.require_auth();
+        Self::validate_config(&config)?;
+        Self::validate_stake(&env, &config)?;
+        Self::charge_fee(&env, &creator, |f| f.create_fee)?;
+        let mut game = Self::new_game(&env, session_id, creator, wager)?;
+        game.config = config;
+        Self::seat_player(&env, &mut game, second_player)?;
+        Self::stake_on_join(&env, &mut game, 0)?;
+        Self::stake_on_join(&env, &mut game, 1)?;
+        Self::store(&env, session_id, &game);
+        Ok(session_id)
+    }
+
+    /// Tournament setup: open `count` lobbies at `base_session_id..base_session_id + count`, each
+    /// with `creator` in slot 0 and the same wager and config. Creates nothing if any id is taken.
+    pub fn create_bracket(
+        env: Env,
+        base_session_id: u32,
+        count: u32,
+        creator: Address,
+        wager: i128,
+        config: GameConfig,
+    ) -> Result<Vec<u32>, MafiaError> {
+        creator.require_auth();
+        if count == 0 || count > MAX_BRACKET { return Err(MafiaError::LimitExceeded); }
+        let end = base_session_id.checked_add(count).ok_or(MafiaError::LimitExceeded)?;
+        Self::validate_config(&config)?;
+        Self::validate_stake(&env, &config)?;
+        for session_id in base_session_id..end {
+            if !Self::session_free_for(&env, session_id, &creator) { return Err(MafiaError::SessionExists); }
+        }
+        let mut ids = Vec::new(&env);
+        for session_id in base_session_id..end {
+            Self::charge_fee(&env, &creator, |f| f.create_fee)?;
+            let mut game = Self::new_game(&env, session_id, creator.clone(), wager)?;
+            game.config = config.clone();
+            Self::stake_on_join(&env, &mut game, 0)?;
+            Self::store(&env, session_id, &game);
+            ids.push_back(session_id);
+        }
+        Ok(ids)
+    }
+
+    /// Creator, in the lobby: give the bot in `slot` a display name of up to MAX_BOT_NAME bytes.
+    pub fn name_bot(env: Env, session_id: u32, creator: Address, slot: u32, name: Bytes) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if slot >= game.config.player_count { return Err(MafiaError::InvalidTarget); }
+        if name.len() > MAX_BOT_NAME { return Err(MafiaError::TextTooLong); }
+        let mut s = game.slots.get(slot).unwrap();
+        if s.addr.is_some() { return Err(MafiaError::InvalidTarget); }
+        s.bot_name = Some(name);
+        game.slots.set(slot, s);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    pub fn join_game(env: Env, session_id: u32, player: Address) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        Self::seat_player(&env, &mut game, player)?;
+        let idx = game.human_count - 1;
+        Self::stake_on_join(&env, &mut game, idx)?;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Lobby only: give up a seat, refunding any escrowed stake; the last seated human moves into
+    /// it so seats stay contiguous. The creator can't leave (SeatLocked); see `cancel_game`.
+    pub fn leave_game(env: Env, session_id: u32, player: Address) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.match_id.is_some() || game.creator == player { return Err(MafiaError::SeatLocked); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        Self::refund_stake(&env, &mut game, idx);
+        let last = game.human_count - 1;
+        game.slots.set(idx, game.slots.get(last).unwrap());
+        game.slots.set(last, Self::new_slot(None));
+        game.human_count = last;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Creator, in the lobby: call the game off, refunding every escrowed stake, and free the id.
+    pub fn cancel_game(env: Env, session_id: u32, creator: Address) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.match_id.is_some() { return Err(MafiaError::SeatLocked); }
+        for i in 0..game.human_count { Self::refund_stake(&env, &mut game, i); }
+        Self::remove_game(&env, session_id, &game);
+        SessionCleared { session_id }.publish(&env);
+        Ok(())
+    }
+
+    /// Lobby only: move a seat from `old` to `new` (joined from the wrong wallet). Both must sign;
+    /// the slot keeps its position, and the creator role moves along with it. A match round's seats
+    /// belong to the match's players, so they can't be swapped (SeatLocked).
+    pub fn swap_address(env: Env, session_id: u32, old: Address, new: Address) -> Result<(), MafiaError> {
+        if old == new { return Err(MafiaError::AlreadyJoined); }
+        old.require_auth();
+        new.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.match_id.is_some() { return Err(MafiaError::SeatLocked); }
+        if Self::find_human_slot(&game, &new).is_some() || game.observers.contains(&new) {
+            return Err(MafiaError::AlreadyJoined);
+        }
+        let idx = Self::find_human_slot(&game, &old).ok_or(MafiaError::NotInGame)?;
+        // A stake belongs to the wallet that paid it: refund `old`, then `new` pays in turn.
+        let staked = game.slots.get(idx).unwrap().staked;
+        Self::refund_stake(&env, &mut game, idx);
+        let mut s = game.slots.get(idx).unwrap();
+        s.addr = Some(new.clone());
+        game.slots.set(idx, s);
+        if staked { Self::take_stake(&env, &mut game, idx, false)?; }
+        if game.creator == old { game.creator = new; }
+        Self::assert_unique_addresses(&game);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Register to spectate. Observers take no seat and can't join or act in this game.
+    pub fn add_observer(env: Env, session_id: u32, observer: Address) -> Result<(), MafiaError> {
+        observer.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if Self::find_human_slot(&game, &observer).is_some() || game.observers.contains(&observer) {
+            return Err(MafiaError::AlreadyJoined);
+        }
+        if game.observers.len() >= MAX_OBSERVERS { return Err(MafiaError::LimitExceeded); }
+        game.observers.push_back(observer);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Creator-only, lobby-only: replace the game's rule options.
+    pub fn configure_game(env: Env, session_id: u32, creator: Address, config: GameConfig) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        Self::validate_config(&config)?;
+        Self::validate_stake(&env, &config)?;
+        if config.player_count < game.human_count { return Err(MafiaError::InvalidConfig); }
+        if config.stake_timing == STAKE_NONE {
+            for i in 0..game.human_count { Self::refund_stake(&env, &mut game, i); }
+        }
+        game.config = config;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Creator, in the lobby: fix (or with None, clear) the seed behind the deal and every AI draw.
+    /// A chosen seed is public, so anyone can predict the roles and bot moves it produces; that is
+    /// the point for transparent tournaments and replays, and a reason not to use it otherwise.
+    pub fn set_rng_seed(env: Env, session_id: u32, creator: Address, seed: Option<BytesN<32>>) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        game.rng_seed = seed;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    pub fn begin_game(env: Env, session_id: u32, caller: Address) -> Result<(), MafiaError> {
+        caller.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.creator != caller { return Err(MafiaError::NotCreator); }
+        if game.phase != PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.human_count < 2 && !Self::single_player_allowed(&env) { return Err(MafiaError::NotEnoughPlayers); }
+        if game.config.stake_timing != STAKE_NONE {
+            for i in 0..game.human_count { Self::take_stake(&env, &mut game, i, true)?; }
+        }
+        Self::seed_prng(&env, &game, session_id, 0, 0);
+        let n = game.config.player_count;
+        let (swaps, roles) = Self::deal(&env, &game);
+        if Self::has_rule(&game, RULE_SHUFFLE_SEATS) {
+            for i in (1..n).rev() {
+                let j = swaps[i as usize];
+                let (a, b) = (game.slots.get(i).unwrap(), game.slots.get(j).unwrap());
+                game.slots.set(i, b);
+                game.slots.set(j, a);
+            }
+        }
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            s.role  = roles[i as usize];
+            s.alive = i < n;
+            s.ability_charges = game.config.ability_charges;
+            game.slots.set(i, s);
+        }
+        if Self::has_rule(&game, RULE_CONFIDANT) {
+            let mut mafia = Vec::new(&env);
+            for i in 0..n {
+                if game.slots.get(i).unwrap().role == ROLE_MAFIA { mafia.push_back(i); }
+            }
+            if let Some(slot) = Self::pick_random(&env, &mafia) {
+                Self::put_side(&env, session_id, &DataKey::ConfidantIntel(session_id), &slot);
+            }
+        }
+        game.phase = PHASE_NIGHT_COMMIT;
+        game.day   = 1;
+        Self::open_phase(&env, &mut game);
+        Self::set_active(&env, session_id, true);
+        if game.match_id.is_none() {
+            let (player1, player2) = Self::hub_players(&game);
+            let (points1, points2) = (Self::hub_points(&game, &player1), Self::hub_points(&game, &player2));
+            Self::hub_client(&env).start_game(
+                &env.current_contract_address(), &session_id,
+                &player1, &player2, &points1, &points2,
+            );
+        }
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// ZK Step 1 (hiding): store commitment = sha256(target_be || nonce_be).
+    /// Auto-advances to PHASE_NIGHT_REVEAL once all alive humans commit.
+    pub fn submit_commitment(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), MafiaError> {
+        player.require_auth();
+        Self::check_commitment(&commitment)?;
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_NIGHT_REVEAL { return Err(MafiaError::CommitWindowClosed); }
+        if game.phase != PHASE_NIGHT_COMMIT { return Err(MafiaError::WrongPhase); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        Self::ensure_unpaused(&env, &mut game)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let mut s = game.slots.get(idx).unwrap();
+        if !s.alive { return Err(MafiaError::NotAlive); }
         if s.submitted { return Err(MafiaError::AlreadyActed); }
+        Self::charge_fee(&env, &player, |f| f.action_fee)?;
+        s.commitment = Some(commitment);
+        s.submitted  = true;
+        game.slots.set(idx, s);
+        if Self::all_alive_humans_submitted(&game) {
+            Self::begin_reveal(&env, &mut game);
+        }
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Replace tonight's commitment while others are still committing. Refused once locked.
+    pub fn update_commitment(env: Env, session_id: u32, player: Address, commitment: BytesN<32>) -> Result<(), MafiaError> {
+        player.require_auth();
+        Self::check_commitment(&commitment)?;
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_NIGHT_COMMIT { return Err(MafiaError::WrongPhase); }
+        Self::ensure_unpaused(&env, &mut game)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let mut s = game.slots.get(idx).unwrap();
+        if !s.alive { return Err(MafiaError::NotAlive); }
+        if s.commitment.is_none() || !s.submitted { return Err(MafiaError::NoCommitment); }
+        if s.locked { return Err(MafiaError::AlreadyActed); }
         s.commitment = Some(commitment);
-        s.submitted  = true;
         game.slots.set(idx, s);
-        if Self::all_alive_humans_submitted(&game) {
-            game.phase = PHASE_NIGHT_REVEAL;
-            for i in 0..MAX_PLAYERS {
-                let mut slot = game.slots.get(i).unwrap();
-                if slot.addr.is_some() { slot.submitted = false; }
-                game.slots.set(i, slot);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Mark tonight's commitment final so `update_commitment` refuses it; revealing is unaffected.
+    pub fn lock_commitment(env: Env, session_id: u32, player: Address) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_NIGHT_COMMIT { return Err(MafiaError::WrongPhase); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let mut s = game.slots.get(idx).unwrap();
+        if s.commitment.is_none() || !s.submitted { return Err(MafiaError::NoCommitment); }
+        s.locked = true;
+        game.slots.set(idx, s);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// ZK Step 2 (binding): reveal target+nonce; contract verifies sha256(target||nonce)==commitment.
+    /// Returns InvalidReveal (#12) on mismatch — cannot change a committed target.
+    pub fn reveal_action(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        target: u32,
+        nonce: u64,
+    ) -> Result<(), MafiaError> {
+        Self::reveal_leaf(&env, session_id, player, target, nonce, Vec::new(&env))
+    }
+
+    /// Reveal against a Merkle-root commitment covering several action components. Only the
+    /// ACTION_COMPONENT leaf, sha256(component||target||nonce), opens it; `path` holds sibling
+    /// hashes from that leaf up. Single-leaf commitments use `reveal_action`.
+    pub fn reveal_action_merkle(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        target: u32,
+        nonce: u64,
+        path: Vec<BytesN<32>>,
+    ) -> Result<(), MafiaError> {
+        if path.len() > MAX_MERKLE_DEPTH { return Err(MafiaError::LimitExceeded); }
+        if path.is_empty() { return Err(MafiaError::InvalidReveal); }
+        Self::reveal_leaf(&env, session_id, player, target, nonce, path)
+    }
+
+    fn reveal_leaf(
+        env: &Env,
+        session_id: u32,
+        player: Address,
+        target: u32,
+        nonce: u64,
+        path: Vec<BytesN<32>>,
+    ) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(env, session_id)?;
+        if game.phase == PHASE_DAY { return Err(MafiaError::RevealWindowClosed); }
+        if game.phase != PHASE_NIGHT_REVEAL { return Err(MafiaError::WrongPhase); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        Self::ensure_unpaused(env, &mut game)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let mut s = game.slots.get(idx).unwrap();
+        if !s.alive { return Err(MafiaError::NotAlive); }
+        if s.submitted { return Err(MafiaError::AlreadyActed); }
+        let stored = s.commitment.clone().ok_or(MafiaError::NoCommitment)?;
+        let (leaf, index) = if path.is_empty() {
+            (Self::compute_commitment(env, target, nonce), 0)
+        } else {
+            (Self::merkle_leaf(env, ACTION_COMPONENT, target, nonce), ACTION_COMPONENT)
+        };
+        if Self::merkle_root(env, leaf, &path, index) != stored { return Err(MafiaError::InvalidReveal); }
+        let action = Self::check_night_target(&game, idx, &s, target)?;
+        if Self::has_rule(&game, RULE_TRANSCRIPT) {
+            let proof = RevealProof { day: game.day, slot: idx, commitment: stored, target, nonce, path, index };
+            Self::append_transcript(env, session_id, proof);
+        }
+        s.action    = action;
+        s.submitted = true;
+        game.slots.set(idx, s);
+        Self::store(env, session_id, &game);
+        Ok(())
+    }
+
+    /// Preflight for a night target before committing: runs the same checks `reveal_action` will,
+    /// against the caller's own slot only. The target itself stays off-chain.
+    pub fn validate_intended_target(env: Env, session_id: u32, player: Address, target: u32) -> Result<(), MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_NIGHT_COMMIT && game.phase != PHASE_NIGHT_REVEAL { return Err(MafiaError::WrongPhase); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let s = game.slots.get(idx).unwrap();
+        if !s.alive { return Err(MafiaError::NotAlive); }
+        Self::check_night_target(&game, idx, &s, target)?;
+        Ok(())
+    }
+
+    /// Day vote (transparent by design — daytime discussion is public).
+    pub fn submit_action(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        target: u32,
+    ) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if game.pending_announcement { return Err(MafiaError::NotYetReady); }
+        Self::ensure_unpaused(&env, &mut game)?;
+        if env.ledger().sequence() < game.votes_open_at { return Err(MafiaError::DiscussionOpen); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let s = game.slots.get(idx).unwrap();
+        if !s.alive { return Err(MafiaError::NotAlive); }
+        if s.submitted { return Err(MafiaError::AlreadyActed); }
+        let action = if target == PASS_TARGET {
+            None
+        } else {
+            if target >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
+            let ts = game.slots.get(target).unwrap();
+            if !ts.alive { return Err(MafiaError::InvalidTarget); }
+            if !game.runoff_candidates.is_empty() && !game.runoff_candidates.contains(target) {
+                return Err(MafiaError::InvalidTarget);
+            }
+            if !game.accused.is_empty() && !game.accused.contains(target) { return Err(MafiaError::InvalidTarget); }
+            Some(target)
+        };
+        let mut ms = game.slots.get(idx).unwrap();
+        ms.action    = action;
+        ms.submitted = true;
+        game.slots.set(idx, ms);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Day-only: publicly claim an investigation result for a past night. One claim per player per day.
+    pub fn claim_investigation(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        day: u32,
+        target: u32,
+        result: bool,
+    ) -> Result<(), MafiaError> {
+        player.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        Self::ensure_unpaused(&env, &mut game)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        if !game.slots.get(idx).unwrap().alive { return Err(MafiaError::NotAlive); }
+        if day == 0 || day > game.day || target >= MAX_PLAYERS || target == idx { return Err(MafiaError::InvalidTarget); }
+        for c in game.claims.iter() {
+            if c.claimer == idx && c.day == day { return Err(MafiaError::AlreadyActed); }
+        }
+        game.claims.push_back(Claim { claimer: idx, day, target, is_mafia: result });
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Store (or replace) a note that `get_sealed_note` only hands out once this player is dead.
+    /// The contract never reads it: clients may encrypt it and publish the key on death.
+    pub fn set_sealed_note(env: Env, session_id: u32, player: Address, note: Bytes) -> Result<(), MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        if !game.slots.get(idx).unwrap().alive { return Err(MafiaError::NotAlive); }
+        if note.len() > MAX_SEALED_NOTE { return Err(MafiaError::TextTooLong); }
+        Self::put_side(&env, session_id, &DataKey::SealedNote(session_id, idx), &note);
+        Ok(())
+    }
+
+    /// A player killed in the last resolution may leave up to MAX_LAST_WORDS bytes, once.
+    pub fn leave_last_words(env: Env, session_id: u32, player: Address, words: Bytes) -> Result<(), MafiaError> {
+        player.require_auth();
+        let Some(mut game) = Self::load_game(&env, session_id) else {
+            // The final day's window outlives `finalize`; the words go into the kept record.
+            let (Some(mut fin), Some(mut sync)) = (Self::load_finalized(&env, session_id), Self::load_final_sync(&env, session_id))
+                else { return Err(Self::missing(&env, session_id)); };
+            let idx = fin.seats.iter().position(|s| s.addr.as_ref() == Some(&player)).ok_or(MafiaError::NotInGame)? as u32;
+            if sync.summary.alive.contains(idx) { return Err(MafiaError::StillAlive); }
+            if words.len() > MAX_LAST_WORDS { return Err(MafiaError::TextTooLong); }
+            Self::write_last_words(&mut sync.graveyard, idx, fin.day, PHASE_OVER, words)?;
+            fin.history = env.crypto().sha256(&sync.graveyard.clone().to_xdr(&env)).into();
+            Self::store_finalized(&env, session_id, &fin, &sync);
+            return Ok(());
+        };
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        if game.slots.get(idx).unwrap().alive { return Err(MafiaError::StillAlive); }
+        if words.len() > MAX_LAST_WORDS { return Err(MafiaError::TextTooLong); }
+        Self::write_last_words(&mut game.graveyard, idx, game.day, game.phase, words)?;
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Creator-only: push back the current phase's grace deadline, up to MAX_DEADLINE_EXTENSION per phase.
+    pub fn extend_deadline(env: Env, session_id: u32, creator: Address, extra_ledgers: u32) -> Result<u32, MafiaError> {
+        creator.require_auth();
+        let mut game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if Self::phase_deadline(&game).is_none() { return Err(MafiaError::WrongPhase); }
+        let total = game.deadline_extension.saturating_add(extra_ledgers);
+        if total > MAX_DEADLINE_EXTENSION { return Err(MafiaError::LimitExceeded); }
+        game.deadline_extension = total;
+        let deadline_ledger = Self::phase_deadline(&game).unwrap();
+        Self::store(&env, session_id, &game);
+        DeadlineExtended { session_id, deadline_ledger }.publish(&env);
+        Ok(deadline_ledger)
+    }
+
+    /// Creator-only: freeze this one game (a player needs a break) until `resume_game`.
+    pub fn pause_game(env: Env, session_id: u32, creator: Address) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        if game.paused { return Err(MafiaError::GamePaused); }
+        if game.pause_used >= MAX_PAUSE_LEDGERS { return Err(MafiaError::LimitExceeded); }
+        let paused_at = env.ledger().sequence();
+        game.paused    = true;
+        game.paused_at = paused_at;
+        Self::store(&env, session_id, &game);
+        GamePaused { session_id, paused_at }.publish(&env);
+        Ok(())
+    }
+
+    /// Creator-only: lift a pause; grace deadlines move back by the time spent paused.
+    pub fn resume_game(env: Env, session_id: u32, creator: Address) -> Result<(), MafiaError> {
+        creator.require_auth();
+        let mut game = Self::load(&env, session_id)?;
+        if game.creator != creator { return Err(MafiaError::NotCreator); }
+        if !game.paused { return Err(MafiaError::WrongPhase); }
+        let paused_for = Self::unpause(&env, &mut game);
+        Self::store(&env, session_id, &game);
+        GameResumed { session_id, paused_for }.publish(&env);
+        Ok(())
+    }
+
+    /// Creator or admin: publish the resolved night under RULE_MORNING_ANNOUNCE. Day voting (and its
+    /// grace clock) only opens once the morning has been announced.
+    pub fn announce(env: Env, session_id: u32, caller: Address) -> Result<(), MafiaError> {
+        let mut game = Self::load(&env, session_id)?;
+        Self::require_creator_or_admin(&env, &game, &caller)?;
+        if !game.pending_announcement { return Err(MafiaError::WrongPhase); }
+        Self::publish_night(&env, session_id, &mut game);
+        Self::pass_host(&env, session_id, &mut game);
+        Self::open_phase(&env, &mut game);
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Creator or admin: move a game, with its transcript, sealed notes and private results, into
+    /// persistent storage so it can't lapse with temporary TTL. Persistent entries cost more rent
+    /// and the game keeps paying it until `restore_game`.
+    pub fn archive_game(env: Env, session_id: u32, caller: Address) -> Result<(), MafiaError> {
+        let key = DataKey::Game(session_id);
+        let game: Game = env.storage().temporary().get(&key).ok_or(MafiaError::GameNotFound)?;
+        Self::require_creator_or_admin(&env, &game, &caller)?;
+        env.storage().temporary().remove(&key);
+        env.storage().persistent().set(&key, &game);
+        env.storage().persistent().extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        for k in Self::side_keys(&env, session_id, &game, true).iter() { Self::move_side(&env, &k, true); }
+        Ok(())
+    }
+
+    /// Creator or admin: move an archived game and its side entries back to temporary storage.
+    pub fn restore_game(env: Env, session_id: u32, caller: Address) -> Result<(), MafiaError> {
+        let key = DataKey::Game(session_id);
+        let game: Game = env.storage().persistent().get(&key).ok_or(MafiaError::GameNotFound)?;
+        Self::require_creator_or_admin(&env, &game, &caller)?;
+        env.storage().persistent().remove(&key);
+        for k in Self::side_keys(&env, session_id, &game, true).iter() { Self::move_side(&env, &k, false); }
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// Admin: delete a finished or abandoned game with its transcript and sealed notes so the
+    /// session id can be created again. A game is abandoned once its grace deadline is
+    /// ABANDON_LEDGERS behind, and a lobby once no seat has been taken for as long; begun games
+    /// without a grace period can only be cleared once over. An abandoned game never settles, so
+    /// its escrowed stakes are refunded.
+    pub fn admin_clear_session(env: Env, session_id: u32) -> Result<(), MafiaError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        let abandoned = Self::phase_deadline(&game)
+            .is_some_and(|d| env.ledger().sequence() >= d.saturating_add(ABANDON_LEDGERS))
+            || Self::lobby_idle(&env, &game);
+        if game.phase != PHASE_OVER && !abandoned { return Err(MafiaError::NotYetReady); }
+        for i in 0..MAX_PLAYERS { Self::refund_stake(&env, &mut game, i); }
+        Self::remove_game(&env, session_id, &game);
+        Self::remove_side(&env, &DataKey::Transcript(session_id));
+        SessionCleared { session_id }.publish(&env);
+        Ok(())
+    }
+
+    /// Anyone, once the game is over: replace it with a compact `FinalizedGame` and free the full
+    /// state. The post-game reads (`full_sync`, `get_graveyard`, `get_payouts`, `get_team_members`,
+    /// `get_reveal_transcript`) and the final last-words window keep working from what is kept.
+    /// Calling again returns the stored summary.
+    pub fn finalize(env: Env, session_id: u32) -> Result<FinalizedGame, MafiaError> {
+        let Some(game) = Self::load_game(&env, session_id) else {
+            return Self::load_finalized(&env, session_id).ok_or(MafiaError::GameNotFound);
+        };
+        if game.phase != PHASE_OVER { return Err(MafiaError::WrongPhase); }
+        let mut seats = Vec::new(&env);
+        let mut roles = Vec::new(&env);
+        for s in game.slots.iter() {
+            roles.push_back(s.role);
+            seats.push_back(Seat { addr: s.addr, bot_name: s.bot_name });
+        }
+        let history = env.crypto().sha256(&game.graveyard.clone().to_xdr(&env)).into();
+        let payouts = Self::payouts(&env, &game)?;
+        let fin = FinalizedGame { winner: game.winner, day: game.day, seats, roles, history, payouts };
+        Self::store_finalized(&env, session_id, &fin, &Self::sync(&env, &game));
+        Self::remove_game(&env, session_id, &game);
+        Ok(fin)
+    }
+
+    pub fn get_finalized(env: Env, session_id: u32) -> Option<FinalizedGame> {
+        Self::load_finalized(&env, session_id)
+    }
+
+    /// The exact preimage `submit_commitment` expects the hash of; sha256 it locally to commit.
+    pub fn commitment_preimage(env: Env, target: u32, nonce: u64) -> Bytes {
+        Self::commitment_bytes(&env, target, nonce)
+    }
+
+    /// Canonical message a client signs to derive its nonce: NONCE_DOMAIN || session_be || day_be || phase_be.
+    pub fn nonce_message(env: Env, session_id: u32, day: u32, phase: u32) -> Bytes {
+        let mut msg = Bytes::from_slice(&env, NONCE_DOMAIN);
+        msg.extend_from_array(&session_id.to_be_bytes());
+        msg.extend_from_array(&day.to_be_bytes());
+        msg.extend_from_array(&phase.to_be_bytes());
+        msg
+    }
+
+    /// True if `target`/`nonce` (e.g. a re-derived nonce) open the player's stored commitment.
+    pub fn verify_derived_commitment(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        target: u32,
+        nonce: u64,
+    ) -> Result<bool, MafiaError> {
+        let game = Self::load(&env, session_id)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let stored = game.slots.get(idx).unwrap().commitment.ok_or(MafiaError::NoCommitment)?;
+        Ok(Self::compute_commitment(&env, target, nonce) == stored)
+    }
+
+    /// Read-only dispute check: would (target, nonce) open `slot`'s stored commitment? Only works
+    /// until the night resolves and commitments are cleared.
+    pub fn recheck_reveal(env: Env, session_id: u32, slot: u32, target: u32, nonce: u64) -> Result<bool, MafiaError> {
+        let game = Self::load(&env, session_id)?;
+        if slot >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
+        let stored = game.slots.get(slot).unwrap().commitment.ok_or(MafiaError::NoCommitment)?;
+        Ok(Self::compute_commitment(&env, target, nonce) == stored)
+    }
+
+    /// Advance phase: PHASE_NIGHT_REVEAL->PHASE_DAY, PHASE_DAY->PHASE_NIGHT_COMMIT.
+    /// With a grace period configured, returns NotYetReady (#14) while a silent human is still
+    /// within it, and can also move a stalled PHASE_NIGHT_COMMIT on to PHASE_NIGHT_REVEAL.
+    /// A commit phase with no living humans can always be moved on.
+    pub fn resolve(env: Env, session_id: u32) -> Result<(), MafiaError> {
+        let mut game: Game = Self::load(&env, session_id)?;
+        Self::advance(&env, session_id, &mut game)?;
+        if Self::has_rule(&game, RULE_AUTO_ADVANCE) { Self::play_out_bots(&env, session_id, &mut game)?; }
+        Self::store(&env, session_id, &game);
+        Ok(())
+    }
+
+    /// True exactly when `resolve` would succeed right now.
+    pub fn can_resolve(env: Env, session_id: u32) -> bool {
+        match Self::load_game(&env, session_id) {
+            Some(game) => Self::check_resolvable(&env, &game).is_ok(),
+            None       => false,
+        }
+    }
+
+    /// Keeper aid: of the active sessions at index positions `start..start + limit` (limit capped
+    /// at MAX_RESOLVE_SCAN), those `resolve` would accept now. Page on to `active_session_count`.
+    pub fn resolvable_sessions(env: Env, start: u32, limit: u32) -> Vec<u32> {
+        let end = start.saturating_add(limit.min(MAX_RESOLVE_SCAN)).min(Self::active_count(&env));
+        let mut ready = Vec::new(&env);
+        for i in start.min(end)..end {
+            let Some(id) = env.storage().temporary().get::<_, u32>(&DataKey::ActiveAt(i)) else { continue };
+            if Self::can_resolve(env.clone(), id) { ready.push_back(id); }
+        }
+        ready
+    }
+
+    /// Keeper aid: drop index positions in `start..start + limit` (limit capped at
+    /// MAX_RESOLVE_SCAN) whose game lapsed or isn't running any more. Each drop moves the last
+    /// entry into the freed position, which is checked next. Returns how many were dropped.
+    pub fn prune_active_sessions(env: Env, start: u32, limit: u32) -> u32 {
+        let (mut pos, mut dropped) = (start, 0);
+        for _ in 0..limit.min(MAX_RESOLVE_SCAN) {
+            if pos >= Self::active_count(&env) { break; }
+            let game = env.storage().temporary().get::<_, u32>(&DataKey::ActiveAt(pos)).and_then(|id| Self::load_game(&env, id));
+            if game.is_some_and(|g| g.phase != PHASE_LOBBY && g.phase != PHASE_OVER) { pos += 1; continue; }
+            Self::drop_active(&env, pos);
+            dropped += 1;
+        }
+        dropped
+    }
+
+    /// Games begun and not yet over, i.e. the index `resolvable_sessions` pages through. Counts
+    /// lapsed games until `prune_active_sessions` clears them.
+    pub fn active_session_count(env: Env) -> u32 {
+        Self::active_count(&env)
+    }
+
+    /// Once every human is dead, play the bots out to PHASE_OVER in one call, for at most
+    /// MAX_AUTO_DAYS further days. Returns the winner, or None if the bound was hit first.
+    pub fn auto_play_remaining(env: Env, session_id: u32) -> Result<Option<u32>, MafiaError> {
+        let mut game: Game = Self::load(&env, session_id)?;
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if Self::humans_alive(&game) { return Err(MafiaError::StillAlive); }
+        Self::play_out_bots(&env, session_id, &mut game)?;
+        Self::store(&env, session_id, &game);
+        Ok(game.winner)
+    }
+
+    /// Every guard `advance` applies before touching the game; `can_resolve` reports the same.
+    fn check_resolvable(env: &Env, game: &Game) -> Result<(), MafiaError> {
+        let grace = game.config.grace_ledgers > 0;
+        let forced = grace || !Self::humans_alive(game);
+        if game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY && !(forced && game.phase == PHASE_NIGHT_COMMIT) {
+            return Err(MafiaError::WrongPhase);
+        }
+        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        if Self::pause_holds(env, game) { return Err(MafiaError::GamePaused); }
+        if game.pending_announcement { return Err(MafiaError::NotYetReady); }
+        if game.phase == PHASE_DAY && env.ledger().sequence() < game.votes_open_at { return Err(MafiaError::NotYetReady); }
+        if grace && !Self::grace_expired(env, game) { return Err(MafiaError::NotYetReady); }
+        Ok(())
+    }
+
+    /// Keep advancing while no human is alive to act, up to game over or MAX_AUTO_DAYS more days.
+    fn play_out_bots(env: &Env, session_id: u32, game: &mut Game) -> Result<(), MafiaError> {
+        let last_day = game.day.saturating_add(MAX_AUTO_DAYS);
+        while game.winner.is_none() && !Self::humans_alive(game) && game.day <= last_day {
+            // No one is left to watch a morning announcement.
+            if game.pending_announcement { Self::publish_night(env, session_id, game); }
+            Self::advance(env, session_id, game)?;
+        }
+        Ok(())
+    }
+
+    fn advance(env: &Env, session_id: u32, game: &mut Game) -> Result<(), MafiaError> {
+        Self::check_resolvable(env, game)?;
+        Self::ensure_unpaused(env, game)?;
+        Self::track_missed(game);
+        if game.phase == PHASE_NIGHT_COMMIT {
+            Self::begin_reveal(env, game);
+            return Ok(());
+        }
+        let graves_before = game.graveyard.len();
+        Self::seed_prng(env, game, session_id, game.day, game.phase);
+        if game.phase == PHASE_NIGHT_REVEAL {
+            Self::resolve_night(env, session_id, game);
+            game.phase = PHASE_DAY;
+            if Self::has_rule(game, RULE_MORNING_ANNOUNCE) {
+                game.pending_announcement = true;
+                game.pending_killed = game.last_killed.take();
+                game.pending_saved  = core::mem::replace(&mut game.last_saved, false);
+            } else {
+                Self::log_night(game);
             }
+        } else if Self::resolve_day(env, game) {
+            game.phase = PHASE_NIGHT_COMMIT;
+            game.day  += 1;
+            Self::recharge(game);
+        }
+        Self::notify_deaths(env, session_id, game, graves_before);
+        // An unannounced death stays secret, so the host hands over only once it is published.
+        if !game.pending_announcement { Self::pass_host(env, session_id, game); }
+        Self::open_phase(env, game);
+        game.winner = Self::check_winner(game);
+        // A lone surviving faction has no one left to lynch, so its win isn't deferred.
+        if game.phase == PHASE_DAY && game.winner != Some(TEAM_TOWN) && Self::has_rule(game, RULE_DAY_BEFORE_PARITY)
+            && Self::sole_faction(game).is_none() {
+            game.winner = None;
+        }
+        if game.winner.is_some() {
+            if game.pending_announcement { Self::publish_night(env, session_id, game); }
+            game.phase = PHASE_OVER;
+            Self::finish_game(env, session_id, game)?;
+        }
+        Ok(())
+    }
+
+    /// Admin debug aid: the action the AI at `slot` will take when the open reveal or day phase
+    /// resolves, drawn from the same seed in the same order. None if that slot has no AI action.
+    pub fn predict_ai_action(env: Env, session_id: u32, slot: u32) -> Result<Option<u32>, MafiaError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        let mut game: Game = Self::load(&env, session_id)?;
+        if slot >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
+        if game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
+        if !Self::ai_plays(&game, &game.slots.get(slot).unwrap()) { return Ok(None); }
+        Self::plan_ai(&env, session_id, &mut game);
+        Ok(game.slots.get(slot).unwrap().action)
+    }
+
+    /// Practice games only: every slot's planned AI action for the open reveal or day phase, as
+    /// `predict_ai_action` would report it; None for slots the AI doesn't play.
+    pub fn ai_intentions(env: Env, session_id: u32) -> Result<Vec<Option<u32>>, MafiaError> {
+        let mut game: Game = Self::load(&env, session_id)?;
+        if !game.config.practice { return Err(MafiaError::RuleDisabled); }
+        if game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
+        let mut bots = [false; MAX_PLAYERS as usize];
+        for i in 0..MAX_PLAYERS { bots[i as usize] = Self::ai_plays(&game, &game.slots.get(i).unwrap()); }
+        Self::plan_ai(&env, session_id, &mut game);
+        let mut out = Vec::new(&env);
+        for i in 0..MAX_PLAYERS {
+            out.push_back(if bots[i as usize] { game.slots.get(i).unwrap().action } else { None });
+        }
+        Ok(out)
+    }
+
+    /// Living players per role, indexed by role. Needs RULE_OPEN_COUNTS until the game is over;
+    /// in hidden-role games it fails with RuleDisabled.
+    pub fn get_surviving_role_counts(env: Env, session_id: u32) -> Result<Vec<u32>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_OVER && !Self::has_rule(&game, RULE_OPEN_COUNTS) { return Err(MafiaError::RuleDisabled); }
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        let mut counts = [0u32; ROLE_COUNT as usize];
+        for s in game.slots.iter() {
+            if s.alive && s.role < ROLE_COUNT { counts[s.role as usize] += 1; }
+        }
+        Ok(Vec::from_array(&env, counts))
+    }
+
+    /// Post-game settlement of the pot (every human's wager): losing humans share
+    /// `consolation_bps` of it, winning humans split the rest, and every rounding remainder goes
+    /// to the first payee listed so the amounts always sum to the pot. If no human won (the bots
+    /// did), the whole pot is returned to the humans evenly.
+    pub fn get_payouts(env: Env, session_id: u32) -> Result<Vec<(Address, i128)>, MafiaError> {
+        let Some(game) = Self::load_game(&env, session_id) else {
+            return Self::load_finalized(&env, session_id).map(|f| f.payouts).ok_or_else(|| Self::missing(&env, session_id));
+        };
+        Self::payouts(&env, &game)
+    }
+
+    fn payouts(env: &Env, game: &Game) -> Result<Vec<(Address, i128)>, MafiaError> {
+        if game.phase != PHASE_OVER { return Err(MafiaError::WrongPhase); }
+        let mut winners = Vec::new(env);
+        let mut losers  = Vec::new(env);
+        for s in game.slots.iter() {
+            let Some(addr) = s.addr else { continue };
+            if game.winner == Some(Self::team_of(s.role)) { winners.push_back(addr); } else { losers.push_back(addr); }
+        }
+        let pot = game.wager.checked_mul(game.human_count as i128).ok_or(MafiaError::Overflow)?;
+        let bps = game.config.consolation_bps as i128;
+        let denom = BPS_DENOMINATOR as i128;
+        // Split into (pot / denom) and (pot % denom) so the product can't overflow.
+        let consolation = if winners.is_empty() { pot }
+            else if losers.is_empty() { 0 }
+            else { pot / denom * bps + pot % denom * bps / denom };
+        let mut payouts = Vec::new(env);
+        Self::share_out(&mut payouts, &losers, consolation);
+        Self::share_out(&mut payouts, &winners, pot - consolation);
+        Ok(payouts)
+    }
+
+    /// The confidant's start-of-game intel: one mafia member's slot. None for every other role.
+    pub fn get_confidant_intel(env: Env, session_id: u32, player: Address) -> Result<Option<u32>, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        if game.slots.get(idx).unwrap().role != ROLE_CONFIDANT { return Ok(None); }
+        Ok(Self::get_side(&env, &DataKey::ConfidantIntel(session_id)))
+    }
+
+    /// Whether the doctor's last save mattered: true if its target was attacked, false if no attack
+    /// came. None for every other role, or when no save was made.
+    pub fn get_doctor_feedback(env: Env, session_id: u32, player: Address) -> Result<Option<bool>, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        if game.slots.get(idx).unwrap().role != ROLE_DOCTOR { return Ok(None); }
+        Ok(Self::get_side(&env, &DataKey::DoctorFeedback(session_id)))
+    }
+
+    /// A mafia player's partners' slots, dead ones included. Empty for everyone else, and for
+    /// the mafia too under RULE_MAFIA_ANONYMOUS.
+    pub fn get_mafia_team(env: Env, session_id: u32, player: Address) -> Result<Vec<u32>, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let mut team = Vec::new(&env);
+        let role = game.slots.get(idx).unwrap().role;
+        if Self::team_of(role) != TEAM_MAFIA || Self::has_rule(&game, RULE_MAFIA_ANONYMOUS) { return Ok(team); }
+        for i in 0..MAX_PLAYERS {
+            if i != idx && Self::team_of(game.slots.get(i).unwrap().role) == TEAM_MAFIA { team.push_back(i); }
+        }
+        Ok(team)
+    }
+
+    /// Whether the player's faction won; neutral roles win only as their own faction. None while
+    /// the game is running or if the player never sat in it; a game over with no winner is a loss.
+    pub fn did_i_win(env: Env, session_id: u32, player: Address) -> Option<bool> {
+        player.require_auth();
+        let Some(game) = Self::load_game(&env, session_id) else {
+            let fin = Self::load_finalized(&env, session_id)?;
+            let idx = fin.seats.iter().position(|s| s.addr.as_ref() == Some(&player))?;
+            return Some(fin.winner == Some(Self::team_of(fin.roles.get(idx as u32).unwrap())));
+        };
+        if game.phase != PHASE_OVER { return None; }
+        let idx = Self::find_human_slot(&game, &player)?;
+        let role = game.slots.get(idx).unwrap().role;
+        Some(game.winner == Some(Self::team_of(role)))
+    }
+
+    /// Full night details under RULE_OPEN_NIGHT; RuleDisabled in games that keep nights private.
+    pub fn get_night_reports(env: Env, session_id: u32) -> Result<Vec<NightReport>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        if !Self::has_rule(&game, RULE_OPEN_NIGHT) { return Err(MafiaError::RuleDisabled); }
+        Ok(Self::announced_reports(&env, &game))
+    }
+
+    pub fn get_constants(_env: Env) -> Constants {
+        Constants {
+            pass_target: PASS_TARGET,
+            max_players: MAX_PLAYERS,
+            min_players: MIN_PLAYERS,
+            role_mafia: ROLE_MAFIA,
+            role_villager: ROLE_VILLAGER,
+            role_doctor: ROLE_DOCTOR,
+            role_sheriff: ROLE_SHERIFF,
+            role_vigilante: ROLE_VIGILANTE,
+            role_serial_killer: ROLE_SERIAL_KILLER,
+            role_confidant: ROLE_CONFIDANT,
+            role_none: ROLE_NONE,
+            phase_lobby: PHASE_LOBBY,
+            phase_night_commit: PHASE_NIGHT_COMMIT,
+            phase_night_reveal: PHASE_NIGHT_REVEAL,
+            phase_day: PHASE_DAY,
+            phase_over: PHASE_OVER,
+            team_mafia: TEAM_MAFIA,
+            team_town: TEAM_TOWN,
+            team_serial_killer: TEAM_SERIAL_KILLER,
         }
-        Self::store(&env, session_id, &game);
-        Ok(())
     }
 
-    /// ZK Step 2 (binding): reveal target+nonce; contract verifies sha256(target||nonce)==commitment.
-    /// Returns InvalidReveal (#12) on mismatch — cannot change a committed target.
-    pub fn reveal_action(
+    /// The ruleset a new game starts with until the creator configures it.
+    pub fn get_default_config(_env: Env) -> GameConfig {
+        Self::default_config()
+    }
+
+    pub fn get_config(env: Env, session_id: u32) -> Result<GameConfig, MafiaError> {
+        Ok(Self::load(&env, session_id)?.config)
+    }
+
+    /// The seed `begin_game` dealt seats and roles from; None until the game has begun.
+    pub fn get_role_seed(env: Env, session_id: u32) -> Result<Option<BytesN<32>>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_LOBBY { return Ok(None); }
+        Ok(Some(Self::prng_seed(&env, &game, session_id, 0, 0)))
+    }
+
+    /// Replay the deal from `proof_seed` and report whether it gives the caller's slot `claimed_role`.
+    /// False if `proof_seed` isn't the game's role seed.
+    pub fn verify_my_role(
         env: Env,
         session_id: u32,
         player: Address,
-        target: u32,
-        nonce: u64,
-    ) -> Result<(), MafiaError> {
+        claimed_role: u32,
+        proof_seed: BytesN<32>,
+    ) -> Result<bool, MafiaError> {
         player.require_auth();
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.phase != PHASE_NIGHT_REVEAL { return Err(MafiaError::WrongPhase); }
-        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
         let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
-        let mut s = game.slots.get(idx).unwrap();
-        if !s.alive { return Err(MafiaError::NotAlive); }
-        if s.submitted { return Err(MafiaError::AlreadyActed); }
-        let stored = s.commitment.clone().ok_or(MafiaError::NoCommitment)?;
-        let computed = Self::compute_commitment(&env, target, nonce);
-        if computed != stored { return Err(MafiaError::InvalidReveal); }
-        let action = if target == PASS_TARGET {
-            None
-        } else {
-            if target >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
-            let ts = game.slots.get(target).unwrap();
-            if !ts.alive { return Err(MafiaError::InvalidTarget); }
-            if target == idx && (s.role == ROLE_MAFIA || s.role == ROLE_SHERIFF) {
-                return Err(MafiaError::InvalidTarget);
+        if proof_seed != Self::prng_seed(&env, &game, session_id, 0, 0) { return Ok(false); }
+        env.prng().seed(proof_seed.into());
+        let (_, roles) = Self::deal(&env, &game);
+        Ok(roles[idx as usize] == claimed_role)
+    }
+
+    /// One of the STATUS_* codes, so routers can pick join, spectate or results without the game.
+    pub fn session_status(env: Env, session_id: u32) -> u32 {
+        if Self::load_finalized(&env, session_id).is_some() { return STATUS_OVER; }
+        match Self::load_game(&env, session_id) {
+            None                              => STATUS_NONEXISTENT,
+            Some(g) if g.phase == PHASE_LOBBY => STATUS_LOBBY,
+            Some(g) if g.phase == PHASE_OVER  => STATUS_OVER,
+            Some(_)                           => STATUS_IN_PROGRESS,
+        }
+    }
+
+    /// Under RULE_ANONYMOUS_VOTES or RULE_BLIND_DAY the day's cast votes are blanked out of `slots`.
+    /// Until the morning is announced the night's deaths, save and report are held back: victims
+    /// read alive and `pending_killed`/`pending_saved` stay empty.
+    pub fn get_game(env: Env, session_id: u32) -> Option<Game> {
+        let mut game = Self::load_game(&env, session_id)?;
+        if game.pending_announcement {
+            let mut graveyard = Vec::new(&env);
+            for g in game.graveyard.clone().iter() {
+                if !Self::unannounced(&game, &g) { graveyard.push_back(g); continue; }
+                let mut s = game.slots.get(g.slot).unwrap();
+                s.alive = true;
+                game.slots.set(g.slot, s);
             }
-            Some(target)
+            game.graveyard        = graveyard;
+            game.night_reports    = Self::announced_reports(&env, &game);
+            game.pending_killed   = None;
+            game.pending_saved    = false;
+            game.last_kill_source = None;
+        }
+        if game.phase == PHASE_DAY && Self::has_rule(&game, RULE_ANONYMOUS_VOTES | RULE_BLIND_DAY) {
+            for i in 0..MAX_PLAYERS {
+                let mut s = game.slots.get(i).unwrap();
+                s.action = None;
+                game.slots.set(i, s);
+            }
+        }
+        Some(game)
+    }
+    /// Post-game only: (slot, role, addr) for every slot on `team`. Roles stay hidden until PHASE_OVER.
+    pub fn get_team_members(env: Env, session_id: u32, team: u32) -> Result<Vec<(u32, u32, Option<Address>)>, MafiaError> {
+        let mut members = Vec::new(&env);
+        let Some(game) = Self::load_game(&env, session_id) else {
+            let fin = Self::load_finalized(&env, session_id).ok_or_else(|| Self::missing(&env, session_id))?;
+            for i in 0..MAX_PLAYERS {
+                let role = fin.roles.get(i).unwrap();
+                if role != ROLE_NONE && Self::team_of(role) == team { members.push_back((i, role, fin.seats.get(i).unwrap().addr)); }
+            }
+            return Ok(members);
         };
-        s.action    = action;
-        s.submitted = true;
-        game.slots.set(idx, s);
-        Self::store(&env, session_id, &game);
-        Ok(())
+        if game.phase != PHASE_OVER { return Err(MafiaError::WrongPhase); }
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.role != ROLE_NONE && Self::team_of(s.role) == team { members.push_back((i, s.role, s.addr)); }
+        }
+        Ok(members)
     }
-
-    /// Day vote (transparent by design — daytime discussion is public).
-    pub fn submit_action(
-        env: Env,
-        session_id: u32,
-        player: Address,
-        target: u32,
-    ) -> Result<(), MafiaError> {
+    /// (revealed, expected) during PHASE_NIGHT_REVEAL: living humans holding a commitment, and how
+    /// many of them have opened it. Counts only, never targets.
+    pub fn reveal_progress(env: Env, session_id: u32) -> Result<(u32, u32), MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_NIGHT_REVEAL { return Err(MafiaError::WrongPhase); }
+        let (mut revealed, mut expected) = (0, 0);
+        for s in game.slots.iter() {
+            if s.addr.is_none() || !s.alive || s.commitment.is_none() { continue; }
+            expected += 1;
+            if s.submitted { revealed += 1; }
+        }
+        Ok((revealed, expected))
+    }
+    /// The caller's own revealed night target, as resolution will apply it (None = PASS). Reads
+    /// only the caller's slot; NotYetReady until they have revealed.
+    pub fn my_night_preview(env: Env, session_id: u32, player: Address) -> Result<Option<u32>, MafiaError> {
         player.require_auth();
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
-        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
+        let game: Game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_NIGHT_REVEAL { return Err(MafiaError::WrongPhase); }
         let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
         let s = game.slots.get(idx).unwrap();
         if !s.alive { return Err(MafiaError::NotAlive); }
-        if s.submitted { return Err(MafiaError::AlreadyActed); }
-        let action = if target == PASS_TARGET {
-            None
-        } else {
-            if target >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
-            let ts = game.slots.get(target).unwrap();
-            if !ts.alive { return Err(MafiaError::InvalidTarget); }
-            Some(target)
+        if !s.submitted { return Err(MafiaError::NotYetReady); }
+        Ok(s.action)
+    }
+    /// Slots of living humans who still owe an action this phase; empty outside commit/reveal/day.
+    pub fn expected_submitters(env: Env, session_id: u32) -> Result<Vec<u32>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        let mut out = Vec::new(&env);
+        if game.phase != PHASE_NIGHT_COMMIT && game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY {
+            return Ok(out);
+        }
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.addr.is_none() || !s.alive || s.submitted { continue; }
+            // A human with nothing committed has nothing to reveal.
+            if game.phase == PHASE_NIGHT_REVEAL && s.commitment.is_none() { continue; }
+            out.push_back(i);
+        }
+        Ok(out)
+    }
+    /// The caller's own watcher result, if they have used their peek.
+    pub fn get_peek_result(env: Env, session_id: u32, player: Address) -> Result<Option<Peek>, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        Ok(Self::get_side(&env, &DataKey::Peek(session_id, idx)))
+    }
+    /// Caller-only: role, phase, legal targets, pass availability and spare one-shot abilities.
+    /// During a night phase targets are filtered by the same rules `reveal_action` enforces.
+    pub fn decision_space(env: Env, session_id: u32, player: Address) -> Result<DecisionSpace, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let s = game.slots.get(idx).unwrap();
+        let mut legal_targets = Vec::new(&env);
+        let night = game.phase == PHASE_NIGHT_COMMIT || game.phase == PHASE_NIGHT_REVEAL;
+        let voting = Self::votes_open(&env, &game);
+        let open = ((night && !Self::pause_holds(&env, &game)) || voting) && s.alive && !s.submitted;
+        if open {
+            for t in 0..MAX_PLAYERS {
+                let ok = if night {
+                    Self::check_night_target(&game, idx, &s, t).is_ok()
+                } else {
+                    game.slots.get(t).unwrap().alive
+                        && (game.runoff_candidates.is_empty() || game.runoff_candidates.contains(t))
+                        && (game.accused.is_empty() || game.accused.contains(t))
+                };
+                if ok { legal_targets.push_back(t); }
+            }
+        }
+        let watcher = s.role == ROLE_VILLAGER && Self::has_rule(&game, RULE_WATCHER) && s.ability_charges > 0
+            && !Self::has_rule(&game, RULE_NO_POWERS);
+        Ok(DecisionSpace {
+            role: s.role,
+            phase: game.phase,
+            legal_targets,
+            can_pass: open && (!night || Self::check_night_target(&game, idx, &s, PASS_TARGET).is_ok()),
+            abilities_left: if watcher { s.ability_charges } else { 0 },
+        })
+    }
+    /// Caller-only: where the caller's seat stands in tonight's commit and reveal, and what it
+    /// must do next.
+    pub fn my_action_state(env: Env, session_id: u32, player: Address) -> Result<ActionState, MafiaError> {
+        player.require_auth();
+        let game: Game = Self::load(&env, session_id)?;
+        let idx = Self::find_human_slot(&game, &player).ok_or(MafiaError::NotInGame)?;
+        let s = game.slots.get(idx).unwrap();
+        let committed = match game.phase {
+            PHASE_NIGHT_COMMIT => s.submitted,
+            PHASE_NIGHT_REVEAL => s.commitment.is_some(),
+            _ => false,
         };
-        let mut ms = game.slots.get(idx).unwrap();
-        ms.action    = action;
-        ms.submitted = true;
-        game.slots.set(idx, ms);
-        Self::store(&env, session_id, &game);
-        Ok(())
+        let revealed = game.phase == PHASE_NIGHT_REVEAL && committed && s.submitted;
+        let owes = s.alive && !s.submitted && game.winner.is_none() && !Self::pause_holds(&env, &game);
+        let next_step = match game.phase {
+            PHASE_NIGHT_COMMIT if owes => STEP_COMMIT,
+            PHASE_NIGHT_REVEAL if owes && committed => STEP_REVEAL,
+            PHASE_DAY if owes && Self::votes_open(&env, &game) => STEP_VOTE,
+            _ => STEP_NONE,
+        };
+        Ok(ActionState { phase: game.phase, committed, revealed, locked: s.locked, ability_charges: s.ability_charges, next_step })
+    }
+    pub fn get_phase_deadline(env: Env, session_id: u32) -> Result<Option<u32>, MafiaError> {
+        Ok(Self::phase_deadline(&Self::load(&env, session_id)?))
+    }
+    /// Public summary. While a morning announcement is pending, tonight's victims still show alive.
+    pub fn get_game_summary(env: Env, session_id: u32) -> Result<GameSummary, MafiaError> {
+        Ok(Self::summary(&env, &Self::load(&env, session_id)?))
+    }
+    /// Cold-start read for a joining client or a recovering indexer: the public summary plus the
+    /// announced graveyard, night log, claims and last tally. `known_roles` shows each slot's role
+    /// only where a grave revealed it, or for everyone once the game is over; ROLE_NONE otherwise.
+    pub fn full_sync(env: Env, session_id: u32) -> Result<FullSync, MafiaError> {
+        let Some(game) = Self::load_game(&env, session_id) else {
+            return Self::load_final_sync(&env, session_id).ok_or_else(|| Self::missing(&env, session_id));
+        };
+        Ok(Self::sync(&env, &game))
     }
 
-    /// Advance phase: PHASE_NIGHT_REVEAL->PHASE_DAY, PHASE_DAY->PHASE_NIGHT_COMMIT.
-    pub fn resolve(env: Env, session_id: u32) -> Result<(), MafiaError> {
-        let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(MafiaError::GameNotFound)?;
-        if game.phase != PHASE_NIGHT_REVEAL && game.phase != PHASE_DAY { return Err(MafiaError::WrongPhase); }
-        if game.winner.is_some() { return Err(MafiaError::GameAlreadyOver); }
-        Self::seed_prng(&env, session_id, game.day, game.phase);
-        if game.phase == PHASE_NIGHT_REVEAL {
-            Self::resolve_night(&env, &mut game);
-            game.phase = PHASE_DAY;
-        } else {
-            Self::resolve_day(&env, &mut game);
-            game.phase = PHASE_NIGHT_COMMIT;
-            game.day  += 1;
+    fn sync(env: &Env, game: &Game) -> FullSync {
+        let mut graveyard = Vec::new(env);
+        for g in game.graveyard.iter() {
+            if !Self::unannounced(game, &g) { graveyard.push_back(g); }
         }
-        let (mut mafia_alive, mut town_alive) = (0u32, 0u32);
+        let mut known_roles = Vec::new(env);
         for i in 0..MAX_PLAYERS {
+            let revealed = graveyard.iter().find(|g| g.slot == i).and_then(|g| g.revealed_role);
+            let role = if game.phase == PHASE_OVER { game.slots.get(i).unwrap().role } else { revealed.unwrap_or(ROLE_NONE) };
+            known_roles.push_back(role);
+        }
+        FullSync {
+            summary: Self::summary(env, game),
+            graveyard,
+            night_log: game.night_log.clone(),
+            claims: game.claims.clone(),
+            last_tally: game.last_tally.clone(),
+            known_roles,
+        }
+    }
+    /// (mafia_needed, town_needed): further deaths each side needs to win from the current board.
+    /// Town needs every mafia and neutral dead; mafia need enough others dead to reach parity, and
+    /// under RULE_NEUTRAL_BLOCKS_PARITY at least the living neutrals too; u32::MAX once no mafia
+    /// are left. Only team totals are used.
+    pub fn win_progress(env: Env, session_id: u32) -> Result<(u32, u32), MafiaError> {
+        let game = Self::load(&env, session_id)?;
+        if game.phase == PHASE_LOBBY { return Err(MafiaError::WrongPhase); }
+        let (mafia, town, neutral) = Self::faction_counts(&game);
+        let mut mafia_needed = (town + neutral).saturating_sub(mafia);
+        if Self::has_rule(&game, RULE_NEUTRAL_BLOCKS_PARITY) { mafia_needed = mafia_needed.max(neutral); }
+        if mafia == 0 { mafia_needed = u32::MAX; }
+        Ok((mafia_needed, mafia + neutral))
+    }
+    /// Post-game only: every reveal with the commitment it opened, for auditing the binding property.
+    pub fn get_reveal_transcript(env: Env, session_id: u32) -> Result<Vec<RevealProof>, MafiaError> {
+        match Self::load_game(&env, session_id) {
+            Some(game) if game.phase != PHASE_OVER => return Err(MafiaError::WrongPhase),
+            None if Self::load_finalized(&env, session_id).is_none() => return Err(Self::missing(&env, session_id)),
+            _ => {}
+        }
+        Ok(Self::get_side(&env, &DataKey::Transcript(session_id)).unwrap_or(Vec::new(&env)))
+    }
+    /// Table composition, one Seat per slot in play. Bot seats read `addr: None` even under
+    /// RULE_MASK_BOTS, which masks submission status only.
+    pub fn get_players(env: Env, session_id: u32) -> Result<Vec<Seat>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        let mut out = Vec::new(&env);
+        for i in 0..game.config.player_count {
             let s = game.slots.get(i).unwrap();
-            if s.alive {
-                if s.role == ROLE_MAFIA { mafia_alive += 1; } else { town_alive += 1; }
+            out.push_back(Seat { addr: s.addr, bot_name: s.bot_name });
+        }
+        Ok(out)
+    }
+    pub fn get_observers(env: Env, session_id: u32) -> Result<Vec<Address>, MafiaError> {
+        Ok(Self::load(&env, session_id)?.observers)
+    }
+    /// Unlocks once the slot's death is public; a night kill awaiting `announce` still reads StillAlive.
+    pub fn get_sealed_note(env: Env, session_id: u32, slot: u32) -> Result<Option<Bytes>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        if slot >= MAX_PLAYERS { return Err(MafiaError::InvalidTarget); }
+        let hidden = game.graveyard.iter().any(|g| g.slot == slot && Self::unannounced(&game, &g));
+        if game.slots.get(slot).unwrap().alive || hidden { return Err(MafiaError::StillAlive); }
+        Ok(Self::get_side(&env, &DataKey::SealedNote(session_id, slot)))
+    }
+    /// (voter, target) for each vote cast so far today; empty outside PHASE_DAY, under
+    /// RULE_BLIND_DAY, where `get_last_tally` shows the outcome once the day resolves, and under
+    /// RULE_ANONYMOUS_VOTES, where `get_day_vote_counts` gives the running totals instead.
+    pub fn get_day_votes_live(env: Env, session_id: u32) -> Result<Vec<(u32, u32)>, MafiaError> {
+        let game = Self::load(&env, session_id)?;
+        let mut votes = Vec::new(&env);
+        if game.phase != PHASE_DAY || Self::has_rule(&game, RULE_BLIND_DAY | RULE_ANONYMOUS_VOTES) { return Ok(votes); }
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if let (true, Some(t)) = (s.submitted, s.action) { votes.push_back((i, t)); }
+        }
+        Ok(votes)
+    }
+    /// Votes cast so far today per target slot; empty outside PHASE_DAY and under RULE_BLIND_DAY.
+    pub fn get_day_vote_counts(env: Env, session_id: u32) -> Result<Vec<u32>, MafiaError> {
+        let game = Self::load(&env, session_id)?;
+        if game.phase != PHASE_DAY || Self::has_rule(&game, RULE_BLIND_DAY) { return Ok(Vec::new(&env)); }
+        let mut counts = [0u32; MAX_PLAYERS as usize];
+        for s in game.slots.iter() {
+            if let (true, true, Some(t)) = (s.alive, s.submitted, s.action) {
+                if let Some(c) = counts.get_mut(t as usize) { *c += 1; }
             }
         }
-        if mafia_alive == 0 { game.winner = Some(TEAM_TOWN); game.phase = PHASE_OVER; }
-        else if mafia_alive >= town_alive { game.winner = Some(TEAM_MAFIA); game.phase = PHASE_OVER; }
-        if let Some(w) = game.winner {
-            Self::hub_client(&env).end_game(&session_id, &(w == TEAM_TOWN));
+        Ok(Vec::from_array(&env, counts))
+    }
+    pub fn get_last_tally(env: Env, session_id: u32) -> Result<Vec<u32>, MafiaError> {
+        Ok(Self::load(&env, session_id)?.last_tally)
+    }
+    /// Announced graves only: tonight's stay out while a morning announcement is pending.
+    pub fn get_graveyard(env: Env, session_id: u32) -> Result<Vec<Grave>, MafiaError> {
+        let Some(game) = Self::load_game(&env, session_id) else {
+            return Self::load_final_sync(&env, session_id).map(|s| s.graveyard).ok_or_else(|| Self::missing(&env, session_id));
+        };
+        let mut graveyard = Vec::new(&env);
+        for g in game.graveyard.iter() {
+            if !Self::unannounced(&game, &g) { graveyard.push_back(g); }
         }
-        Self::store(&env, session_id, &game);
-        Ok(())
+        Ok(graveyard)
     }
-
-    pub fn get_game(env: Env, session_id: u32) -> Option<Game> {
-        env.storage().temporary().get(&DataKey::Game(session_id))
+    pub fn get_claims(env: Env, session_id: u32) -> Result<Vec<Claim>, MafiaError> {
+        let game: Game = Self::load(&env, session_id)?;
+        Ok(game.claims)
+    }
+    /// Short description of a role's night action; empty for unknown roles.
+    pub fn role_action_hint(env: Env, role: u32) -> Bytes {
+        let text: &[u8] = match role {
+            ROLE_MAFIA         => b"Choose a town player to kill",
+            ROLE_VILLAGER      => b"No night action; pass (or watch a player once with RULE_WATCHER)",
+            ROLE_DOCTOR        => b"Choose a player to protect from tonight's kills",
+            ROLE_SHERIFF       => b"Choose a player to investigate",
+            ROLE_VIGILANTE     => b"Choose a player to shoot; shooting town costs your life",
+            ROLE_SERIAL_KILLER => b"Choose any other player to kill; you win alone",
+            ROLE_CONFIDANT     => b"No night action; you were shown one mafia member at the start",
+            _                  => b"",
+        };
+        Bytes::from_slice(&env, text)
+    }
+    /// Role ids this build implements, each with a `role_action_hint`. Extend it with every new role.
+    pub fn supported_roles(env: Env) -> Vec<u32> {
+        Vec::from_array(&env, [
+            ROLE_MAFIA, ROLE_VILLAGER, ROLE_DOCTOR, ROLE_SHERIFF, ROLE_VIGILANTE, ROLE_SERIAL_KILLER, ROLE_CONFIDANT,
+        ])
+    }
+    pub fn get_stats(env: Env, player: Address) -> PlayerStats {
+        Self::load_stats(&env, &player)
+    }
+    /// Admin-only: mafia kill attempts, doctor saves of those kills, investigations and lynches.
+    pub fn get_balance_stats(env: Env) -> BalanceStats {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        Self::load_balance(&env)
+    }
+    /// Per role: finished games it was dealt in, and how many of those its team won.
+    pub fn get_role_stats(env: Env) -> RoleStats {
+        Self::load_role_stats(&env)
     }
     pub fn get_admin(env: Env) -> Address {
         env.storage().instance().get(&DataKey::Admin).unwrap()
@@ -399,50 +3033,180 @@ impl MafiaDuelContract {
         admin.require_auth();
         env.storage().instance().set(&DataKey::Admin, &new_admin);
     }
+    pub fn get_wager_limits(env: Env) -> (i128, i128) {
+        Self::wager_limits(&env)
+    }
+    pub fn set_wager_limits(env: Env, min_wager: i128, max_wager: i128) -> Result<(), MafiaError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        if min_wager < 0 || min_wager > max_wager { return Err(MafiaError::InvalidWager); }
+        env.storage().instance().set(&DataKey::WagerLimits, &(min_wager, max_wager));
+        Ok(())
+    }
+    pub fn get_fees(env: Env) -> Option<Fees> {
+        env.storage().instance().get(&DataKey::Fees)
+    }
+    /// Admin: set the fee schedule, or with None make every action free again.
+    pub fn set_fees(env: Env, fees: Option<Fees>) -> Result<(), MafiaError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        match fees {
+            Some(f) if f.create_fee < 0 || f.action_fee < 0 => return Err(MafiaError::InvalidConfig),
+            Some(f) => env.storage().instance().set(&DataKey::Fees, &f),
+            None    => env.storage().instance().remove(&DataKey::Fees),
+        }
+        Ok(())
+    }
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
+    /// Admin: where fees are paid.
+    pub fn set_treasury(env: Env, treasury: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+    }
+    /// Admin: false makes this deployment multiplayer-only; `begin_game` then needs a second human.
+    pub fn set_single_player_allowed(env: Env, allowed: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::SinglePlayerAllowed, &allowed);
+    }
+    pub fn get_single_player_allowed(env: Env) -> bool {
+        Self::single_player_allowed(&env)
+    }
     pub fn set_hub(env: Env, new_hub: Address) {
         let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
         env.storage().instance().set(&DataKey::GameHubAddress, &new_hub);
     }
+    /// Admin: contract whose `on_death` hears of every death, or None to stop notifying.
+    pub fn set_death_hook(env: Env, hook: Option<Address>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        match hook {
+            Some(addr) => env.storage().instance().set(&DataKey::DeathHook, &addr),
+            None       => env.storage().instance().remove(&DataKey::DeathHook),
+        }
+    }
     pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
         let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
         env.deployer().update_current_contract_wasm(new_wasm_hash);
     }
 
-    fn resolve_night(env: &Env, game: &mut Game) {
+    /// Draw every AI-played night action from the already-seeded PRNG, in slot order.
+    fn fill_ai_night(env: &Env, game: &mut Game) {
         let (living_all, living_town) = Self::living_lists(env, game);
+        let mut mafia_options = living_town.clone();
+        if !Self::has_rule(game, RULE_MAFIA_MUST_KILL) { mafia_options.push_back(PASS_TARGET); }
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
-            if !s.alive || s.submitted || s.addr.is_some() { continue; }
+            if !Self::ai_plays(game, &s) { continue; }
+            let powerless = s.role != ROLE_MAFIA && Self::has_rule(game, RULE_NO_POWERS);
             let action = match s.role {
-                ROLE_MAFIA   => Self::pick_random(env, &living_town),
-                ROLE_DOCTOR  => Self::pick_random(env, &living_all),
-                ROLE_SHERIFF => Self::pick_excluding(env, &living_all, i),
-                _            => None,
+                _ if powerless => None,
+                ROLE_MAFIA  => Self::pick_random(env, &mafia_options).filter(|&t| t != PASS_TARGET),
+                ROLE_DOCTOR => Self::pick_random(env, &living_all),
+                ROLE_SHERIFF | ROLE_SERIAL_KILLER => Self::pick_excluding(env, &living_all, i),
+                _           => None,
             };
             let mut us = s;
             us.action    = action;
             us.submitted = true;
             game.slots.set(i, us);
         }
-        let mut kill_target: Option<u32> = None;
+    }
+
+    /// Draw every AI-played day vote from the already-seeded PRNG, in slot order.
+    fn fill_ai_day(env: &Env, game: &mut Game) {
+        let ballot = Self::day_ballot(env, game);
+        let suspect = if Self::has_rule(game, RULE_AI_SUSPICION) && game.invest_is_mafia { game.last_investigated } else { None };
+        let mut non_mafia = Vec::new(env);
+        for t in ballot.iter() {
+            if game.slots.get(t).is_some_and(|x| x.role != ROLE_MAFIA) { non_mafia.push_back(t); }
+        }
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
-            if s.alive && s.role == ROLE_MAFIA {
-                if let Some(t) = s.action { kill_target = Some(t); break; }
+            if !Self::ai_plays(game, &s) { continue; }
+            let action = match suspect {
+                // Mafia protect the whole team, falling back to the plain ballot if only mafia remain on it.
+                Some(_) if s.role == ROLE_MAFIA && !non_mafia.is_empty() => Self::pick_random(env, &non_mafia),
+                Some(x) if s.role != ROLE_MAFIA => Self::pick_weighted(env, &ballot, i, x, AI_SUSPICION_WEIGHT),
+                _ => Self::pick_excluding(env, &ballot, i),
+            }.filter(|&t| t != PASS_TARGET);
+            let mut us = s; us.action = action; us.submitted = true;
+            game.slots.set(i, us);
+        }
+    }
+
+    fn day_ballot(env: &Env, game: &Game) -> Vec<u32> {
+        if !game.runoff_candidates.is_empty() { return game.runoff_candidates.clone(); }
+        if !game.accused.is_empty() {
+            let mut ballot = game.accused.clone();
+            ballot.push_back(PASS_TARGET);
+            return ballot;
+        }
+        let mut ballot = Vec::new(env);
+        for i in 0..MAX_PLAYERS {
+            if game.slots.get(i).unwrap().alive { ballot.push_back(i); }
+        }
+        ballot
+    }
+
+    /// The night's mafia kill. Members who pass don't count; a split between the rest is settled
+    /// by `mafia_tie_breaker`, and only a split draws from the PRNG. Under RULE_MAFIA_MUST_KILL a
+    /// night no living mafia named a target for (none revealed) gets an AI-drawn kill instead.
+    fn mafia_kill_target(env: &Env, game: &Game) -> Option<u32> {
+        let mut targets = Vec::new(env);
+        let mut mafia_alive = false;
+        for s in game.slots.iter() {
+            if !s.alive || s.role != ROLE_MAFIA { continue; }
+            mafia_alive = true;
+            if let Some(t) = s.action {
+                if !targets.contains(t) { targets.push_back(t); }
             }
         }
+        if targets.is_empty() && mafia_alive && Self::has_rule(game, RULE_MAFIA_MUST_KILL) {
+            return Self::pick_random(env, &Self::living_lists(env, game).1);
+        }
+        if targets.len() <= 1 { return targets.first(); }
+        match game.config.mafia_tie_breaker {
+            MAFIA_TIE_NO_KILL => None,
+            MAFIA_TIE_FIRST   => targets.first(),
+            _                 => Self::pick_random(env, &targets),
+        }
+    }
+
+    fn resolve_night(env: &Env, session_id: u32, game: &mut Game) {
+        Self::fill_ai_night(env, game);
+        let kill_target = Self::mafia_kill_target(env, game);
         let mut save_target: Option<u32> = None;
+        let mut doctor: Option<u32> = None;
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.alive && s.role == ROLE_DOCTOR { save_target = s.action; doctor = Some(i); break; }
+        }
+        let mut vig_shot: Option<(u32, u32)> = None;
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if s.alive && s.role == ROLE_VIGILANTE {
+                if let Some(t) = s.action { vig_shot = Some((i, t)); }
+                break;
+            }
+        }
+        let mut sk_target: Option<u32> = None;
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
-            if s.alive && s.role == ROLE_DOCTOR { save_target = s.action; break; }
+            if s.alive && s.role == ROLE_SERIAL_KILLER { sk_target = s.action; break; }
         }
         let mut invest_target: Option<u32> = None;
         let mut invest_is_mafia = false;
+        let mut sheriff: Option<u32> = None;
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
             if s.alive && s.role == ROLE_SHERIFF {
+                sheriff = Some(i);
                 if let Some(t) = s.action {
                     invest_target   = Some(t);
                     invest_is_mafia = game.slots.get(t).unwrap().role == ROLE_MAFIA;
@@ -450,64 +3214,306 @@ impl MafiaDuelContract {
                 break;
             }
         }
+        if Self::has_rule(game, RULE_OPEN_NIGHT) {
+            game.night_reports.push_back(NightReport {
+                day: game.day,
+                mafia_target: kill_target,
+                doctor_target: save_target,
+                sheriff_target: invest_target,
+                sheriff_found_mafia: invest_is_mafia,
+                vigilante_target: vig_shot.map(|(_, t)| t),
+                serial_killer_target: sk_target,
+            });
+        }
+        // A save on someone no one attacked tonight may give the doctor away; draws from the
+        // night's seed, and only then, so other games' sequences are untouched.
+        let feedback = DataKey::DoctorFeedback(session_id);
+        Self::remove_side(env, &feedback);
+        if let (Some(d), Some(t)) = (doctor, save_target) {
+            let attacked = kill_target == Some(t) || sk_target == Some(t) || vig_shot.is_some_and(|(_, v)| v == t);
+            Self::put_side(env, session_id, &feedback, &attacked);
+            if !attacked && Self::has_rule(game, RULE_DOCTOR_EXPOSURE) && game.doctor_exposed.is_none()
+                && env.prng().gen_range::<u64>(0..100) < DOCTOR_EXPOSE_PCT {
+                game.doctor_exposed = Some(d);
+            }
+        }
+        let mut balance = Self::load_balance(env);
+        if kill_target.is_some() { balance.kills_attempted = balance.kills_attempted.saturating_add(1); }
+        if kill_target.is_some() && kill_target == save_target {
+            balance.saves_succeeded = balance.saves_succeeded.saturating_add(1);
+        }
+        if invest_target.is_some() { balance.investigations = balance.investigations.saturating_add(1); }
+        Self::store_balance(env, &balance);
         game.last_killed       = kill_target;
         game.last_saved        = false;
-        game.last_voted_out    = None;
+        game.last_voted_out    = Vec::new(env);
         game.last_investigated = invest_target;
         game.invest_is_mafia   = invest_is_mafia;
         if let Some(ki) = kill_target {
             if save_target == Some(ki) {
                 game.last_saved = true;
             } else {
-                let mut ds = game.slots.get(ki).unwrap();
-                ds.alive = false;
-                game.slots.set(ki, ds);
+                Self::kill(game, ki, DEATH_NIGHT_KILL);
             }
         }
-        for i in 0..MAX_PLAYERS {
-            let mut s = game.slots.get(i).unwrap();
-            s.action = None; s.submitted = false; s.commitment = None;
-            game.slots.set(i, s);
+        // The serial killer strikes alongside the mafia; the doctor's save covers either kill.
+        if let Some(t) = sk_target {
+            if save_target != Some(t) && game.slots.get(t).unwrap().alive { Self::kill(game, t, DEATH_SERIAL_KILL); }
+        }
+        // Watchers see whether their target acted tonight, AI fill included; the peek spends a charge.
+        if Self::has_rule(game, RULE_WATCHER) {
+            for i in 0..MAX_PLAYERS {
+                let mut s = game.slots.get(i).unwrap();
+                if !s.alive || s.role != ROLE_VILLAGER || s.ability_charges == 0 { continue; }
+                let Some(t) = s.action else { continue };
+                let peek = Peek { target: t, acted: game.slots.get(t).unwrap().action.is_some() };
+                // Only a human can ask for the result.
+                if s.addr.is_some() { Self::put_side(env, session_id, &DataKey::Peek(session_id, i), &peek); }
+                s.ability_charges -= 1;
+                game.slots.set(i, s);
+            }
+        }
+        // The vigilante's shot lands after the mafia kill and is blocked by the same doctor save.
+        // Shooting a town member is fatal to the vigilante whether or not the shot landed;
+        // under RULE_VIG_BACKFIRE_SAVABLE the doctor protecting the vigilante prevents that too.
+        if let Some((v, t)) = vig_shot {
+            let target = game.slots.get(t).unwrap();
+            if target.alive && save_target != Some(t) { Self::kill(game, t, DEATH_VIGILANTE); }
+            if Self::team_of(target.role) == TEAM_TOWN {
+                let spared = save_target == Some(v) && Self::has_rule(game, RULE_VIG_BACKFIRE_SAVABLE);
+                if !spared && game.slots.get(v).unwrap().alive { Self::kill(game, v, DEATH_BACKFIRE); }
+            }
+        }
+        game.last_kill_source = None;
+        if Self::has_rule(game, RULE_OPEN_NIGHT) {
+            let first = game.graveyard.iter().find(|g| g.day == game.day && g.cause != DEATH_LYNCH);
+            game.last_kill_source = first.map(|g| match g.cause {
+                DEATH_NIGHT_KILL  => ROLE_MAFIA,
+                DEATH_SERIAL_KILL => ROLE_SERIAL_KILLER,
+                _ => ROLE_VIGILANTE,
+            });
         }
+        // Strict: the kill lands before the sheriff's check completes, so a dead sheriff learns nothing.
+        if let Some(sh) = sheriff {
+            if Self::has_rule(game, RULE_STRICT_SHERIFF) && !game.slots.get(sh).unwrap().alive {
+                game.last_investigated = None;
+                game.invest_is_mafia   = false;
+            }
+        }
+        Self::clear_actions(game);
     }
 
-    fn resolve_day(env: &Env, game: &mut Game) {
-        let mut living = Vec::new(env);
-        for i in 0..MAX_PLAYERS {
-            if game.slots.get(i).unwrap().alive { living.push_back(i); }
-        }
+    /// Returns false when a tie opened a runoff and the day stays open for another vote.
+    fn resolve_day(env: &Env, game: &mut Game) -> bool {
+        Self::fill_ai_day(env, game);
+        let mut counts = [0u32; MAX_PLAYERS as usize];
         for i in 0..MAX_PLAYERS {
             let s = game.slots.get(i).unwrap();
-            if !s.alive || s.submitted || s.addr.is_some() { continue; }
-            let action = Self::pick_excluding(env, &living, i);
-            let mut us = s; us.action = action; us.submitted = true;
-            game.slots.set(i, us);
+            if !s.alive { continue; }
+            let Some(t) = s.action else { continue };
+            // Targets are validated on submission; never let a stray index trap the tally.
+            debug_assert!(t < MAX_PLAYERS);
+            if let Some(c) = counts.get_mut(t as usize) { *c += 1; }
         }
-        let mut counts = [0u32; 8];
-        for i in 0..MAX_PLAYERS {
-            let s = game.slots.get(i).unwrap();
-            if s.alive {
-                if let Some(t) = s.action { counts[t as usize] += 1; }
+        game.last_tally = Vec::from_array(env, counts);
+        if !game.accused.is_empty() {
+            let living = game.slots.iter().filter(|s| s.alive).count() as u32;
+            let mut out = Vec::new(env);
+            for a in game.accused.iter() {
+                if counts[a as usize] * 2 > living { out.push_back(a); }
+            }
+            game.accused = Vec::new(env);
+            return Self::lynch(env, game, out);
+        }
+        // Take the most-voted slots level by level until `day_eliminations` are out; a tie that
+        // straddles the cutoff goes to the tie breaker. A runoff only opens for a tie at the very
+        // top; once anyone is already out, a straddling tie under TIE_RUNOFF eliminates no one more.
+        let wanted = game.config.day_eliminations;
+        let mut out: Vec<u32> = Vec::new(env);
+        let mut ceiling = u32::MAX;
+        while out.len() < wanted {
+            let mut level = 0u32;
+            for (i, &c) in counts.iter().enumerate() {
+                if c < ceiling && c > level && game.slots.get(i as u32).unwrap().alive { level = c; }
+            }
+            if level == 0 { break; }
+            let mut tied: Vec<u32> = Vec::new(env);
+            for (i, &c) in counts.iter().enumerate() {
+                if c == level && game.slots.get(i as u32).unwrap().alive { tied.push_back(i as u32); }
             }
+            ceiling = level;
+            let room = wanted - out.len();
+            if tied.len() <= room {
+                out.append(&tied);
+                continue;
+            }
+            match game.config.tie_breaker {
+                TIE_FIRST_SLOT => out.append(&tied.slice(0..room)),
+                TIE_RANDOM | TIE_SUSPECT => {
+                    let mut room = room;
+                    let suspect = if game.invest_is_mafia { game.last_investigated } else { None };
+                    let flagged = suspect.and_then(|x| tied.first_index_of(x));
+                    if let (TIE_SUSPECT, Some(pos)) = (game.config.tie_breaker, flagged) {
+                        out.push_back(tied.get(pos).unwrap());
+                        tied.remove(pos);
+                        room -= 1;
+                    }
+                    for _ in 0..room {
+                        let pick = Self::pick_random(env, &tied).unwrap();
+                        tied.remove(tied.first_index_of(pick).unwrap());
+                        out.push_back(pick);
+                    }
+                }
+                TIE_RUNOFF if out.is_empty() && game.runoff_round < MAX_RUNOFF_ROUNDS => {
+                    game.runoff_candidates = tied;
+                    game.runoff_round += 1;
+                    Self::clear_actions(game);
+                    return false;
+                }
+                _ => {}
+            }
+            break;
         }
-        let mut max_v = 0u32;
-        let mut elim: Option<u32> = None;
-        for i in 0..8usize {
-            let s = game.slots.get(i as u32).unwrap();
-            if s.alive && counts[i] > max_v { max_v = counts[i]; elim = Some(i as u32); }
+        game.runoff_candidates = Vec::new(env);
+        game.runoff_round      = 0;
+        if Self::has_rule(game, RULE_JUDGMENT) && !out.is_empty() {
+            game.accused = out;
+            Self::clear_actions(game);
+            return false;
         }
-        game.last_voted_out = elim;
+        Self::lynch(env, game, out)
+    }
+
+    /// Eliminate the day's `out` and close the day.
+    fn lynch(env: &Env, game: &mut Game, out: Vec<u32>) -> bool {
         game.last_killed    = None;
+        game.last_kill_source = None;
         game.last_saved     = false;
-        if let Some(ei) = elim {
-            let mut ds = game.slots.get(ei).unwrap();
-            ds.alive = false;
-            game.slots.set(ei, ds);
+        for ei in out.iter() {
+            Self::kill(game, ei, DEATH_LYNCH);
+        }
+        if !out.is_empty() {
+            let mut balance = Self::load_balance(env);
+            balance.lynches = balance.lynches.saturating_add(out.len());
+            Self::store_balance(env, &balance);
+        }
+        game.last_voted_out = out;
+        Self::clear_actions(game);
+        true
+    }
+
+    /// Split `amount` evenly over `payees`, the first taking the remainder.
+    fn share_out(payouts: &mut Vec<(Address, i128)>, payees: &Vec<Address>, amount: i128) {
+        if payees.is_empty() { return; }
+        let count = payees.len() as i128;
+        for (i, addr) in payees.iter().enumerate() {
+            let extra = if i == 0 { amount % count } else { 0 };
+            payouts.push_back((addr, amount / count + extra));
+        }
+    }
+
+    /// If the host died, hand `creator` to the next living human in seat order after them, or to
+    /// the admin once no human is left alive.
+    fn pass_host(env: &Env, session_id: u32, game: &mut Game) {
+        let Some(seat) = Self::find_human_slot(game, &game.creator) else { return };
+        if game.slots.get(seat).unwrap().alive { return; }
+        let heir = (1..MAX_PLAYERS)
+            .map(|k| game.slots.get((seat + k) % MAX_PLAYERS).unwrap())
+            .find(|s| s.alive && s.addr.is_some())
+            .and_then(|s| s.addr);
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        game.creator = heir.unwrap_or(admin);
+        HostChanged { session_id, host: game.creator.clone() }.publish(env);
+    }
+
+    /// Best effort: a hook that fails or traps is ignored so it can never stall a game.
+    fn notify_deaths(env: &Env, session_id: u32, game: &Game, from: u32) {
+        let Some(addr) = env.storage().instance().get::<_, Address>(&DataKey::DeathHook) else { return };
+        let hook = DeathHookClient::new(env, &addr);
+        for i in from..game.graveyard.len() {
+            let grave = game.graveyard.get(i).unwrap();
+            // Withheld by the morning announcement; `publish_night` reports it.
+            if Self::unannounced(game, &grave) { continue; }
+            let role = game.slots.get(grave.slot).unwrap().role;
+            let _ = hook.try_on_death(&session_id, &grave.slot, &role, &grave.cause);
+        }
+    }
+
+    /// Night reports without the one a pending morning announcement still holds.
+    fn announced_reports(env: &Env, game: &Game) -> Vec<NightReport> {
+        let mut out = Vec::new(env);
+        for r in game.night_reports.iter() {
+            if !(game.pending_announcement && r.day == game.day) { out.push_back(r); }
+        }
+        out
+    }
+
+    /// A night death the morning announcement hasn't published yet.
+    fn unannounced(game: &Game, g: &Grave) -> bool {
+        game.pending_announcement && g.day == game.day && g.cause != DEATH_LYNCH
+    }
+
+    fn summary(env: &Env, game: &Game) -> GameSummary {
+        let mut alive = Vec::new(env);
+        for i in 0..MAX_PLAYERS {
+            let hidden = game.graveyard.iter().any(|g| g.slot == i && Self::unannounced(game, &g));
+            if game.slots.get(i).unwrap().alive || hidden { alive.push_back(i); }
         }
+        GameSummary {
+            phase: game.phase,
+            day: game.day,
+            winner: game.winner,
+            alive,
+            last_killed: game.last_killed,
+            last_saved: game.last_saved,
+            last_voted_out: game.last_voted_out.clone(),
+            pending_announcement: game.pending_announcement,
+            last_kill_source: if game.pending_announcement { None } else { game.last_kill_source },
+        }
+    }
+
+    fn clear_actions(game: &mut Game) {
         for i in 0..MAX_PLAYERS {
             let mut s = game.slots.get(i).unwrap();
-            s.action = None; s.submitted = false; s.commitment = None;
+            s.action = None; s.submitted = false; s.commitment = None; s.locked = false;
             game.slots.set(i, s);
         }
     }
 }
+
+#[cfg(test)]
+impl MafiaDuelContract {
+    /// Test-only structural check of a stored game, for property tests to run after every call.
+    pub(crate) fn check_invariants(env: &Env, session_id: u32) -> bool {
+        let Some(game) = Self::load_game(env, session_id) else { return false };
+        let n = game.config.player_count;
+        if game.phase > PHASE_OVER || game.slots.len() != MAX_PLAYERS { return false; }
+        if game.winner.is_some() && game.phase != PHASE_OVER { return false; }
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&n) { return false; }
+        let mut humans = 0;
+        let mut mafia = 0;
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            if let Some(a) = s.addr.as_ref() {
+                humans += 1;
+                for j in (i + 1)..MAX_PLAYERS {
+                    if game.slots.get(j).unwrap().addr.as_ref() == Some(a) { return false; }
+                }
+            }
+            if game.phase == PHASE_LOBBY {
+                if s.submitted { return false; }
+                continue;
+            }
+            if i >= n && (s.alive || s.role != ROLE_NONE) { return false; }
+            if i < n && s.role >= ROLE_COUNT { return false; }
+            if s.role == ROLE_MAFIA { mafia += 1; }
+            if !s.alive && s.submitted { return false; }
+            if game.phase == PHASE_NIGHT_COMMIT && s.submitted && s.commitment.is_none() { return false; }
+        }
+        if humans != game.human_count { return false; }
+        game.phase == PHASE_LOBBY || mafia == n / 3
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -1 +1,4232 @@
-// Tests for 8-player Mafia Duel — coming soon.
+#![cfg(test)]
+
+// Unit tests for the 8-player Mafia Duel contract using a simple mock GameHub.
+// Most tests seat eight humans so every role is driven explicitly by the test.
+
+use crate::{
+    BalanceStats, DataKey, NightRecord, DeadlineExtended, GameConfig, GamePaused, GameResumed, MAX_PAUSE_LEDGERS, MAX_OBSERVERS, MAX_SEALED_NOTE, MAX_DEADLINE_EXTENSION, MAX_MERKLE_DEPTH, ACTION_COMPONENT, MafiaDuelContract, Peek, MafiaDuelContractClient, MafiaError, MAX_PLAYERS, PASS_TARGET,
+    PHASE_DAY, PHASE_LOBBY, PHASE_NIGHT_COMMIT, PHASE_NIGHT_REVEAL, PHASE_OVER, ROLE_MAFIA, RULE_TEAM_KILL, TEAM_MAFIA, TEAM_TOWN,
+    DEATH_BACKFIRE, DEATH_LYNCH, DEATH_NIGHT_KILL, DEATH_VIGILANTE, MAX_LAST_WORDS, ROLE_DOCTOR,
+    ROLE_VIGILANTE, RULE_VIGILANTE, RULE_VIG_BACKFIRE_SAVABLE, TIE_FIRST_SLOT, TIE_NO_ELIM, TIE_RUNOFF,
+    ROLE_VILLAGER, RULE_WATCHER, ROLE_COUNT, ROLE_SHERIFF, ROLE_SERIAL_KILLER, RULE_SERIAL_KILLER,
+    RULE_NEUTRAL_BLOCKS_PARITY, TEAM_SERIAL_KILLER, DEATH_SERIAL_KILL, RULE_MORNING_ANNOUNCE, RULE_ROLE_POINTS, RULE_TRANSCRIPT, RULE_SHUFFLE_SEATS, RULE_MASK_BOTS, ROLE_NONE, RULE_AUTO_ADVANCE, RULE_BLIND_DAY, RULE_REVEAL_ON_LYNCH, RULE_REVEAL_ON_NIGHTKILL, RULE_AI_SUSPICION, ABANDON_LEDGERS, RULE_MAFIA_MUST_KILL, MAX_BRACKET, STATUS_NONEXISTENT, STATUS_LOBBY, STATUS_IN_PROGRESS, STATUS_OVER, RULE_NO_POWERS, TIE_RANDOM, TIE_SUSPECT, RULE_OPEN_COUNTS, MAX_BOT_NAME, Seat, RULE_DAY_BEFORE_PARITY, MIN_PLAYERS, RULE_OPEN_NIGHT, NightReport, RULE_ANONYMOUS_VOTES, Game, RULE_STRICT_SHERIFF, RULE_AFK_BOTIFY, AFK_PHASE_LIMIT, MAX_MATCH_ROUNDS, RoleStats, PlayerStats, MAX_RESOLVE_SCAN, RULE_MAFIA_ANONYMOUS, RULE_DOCTOR_EXPOSURE, RULE_CONFIDANT, ROLE_CONFIDANT, HostChanged, RULE_JUDGMENT, MAFIA_TIE_RANDOM, MAFIA_TIE_NO_KILL, MAFIA_TIE_FIRST, Fees, ActionState, STEP_NONE, STEP_COMMIT, STEP_REVEAL, STEP_VOTE, GAME_TTL_LEDGERS, STAKE_NONE, STAKE_ON_JOIN, STAKE_ON_BEGIN,
+};
+use soroban_sdk::testutils::{storage::{Persistent as _, Temporary as _}, Address as _, Events as _, Ledger as _};
+use soroban_sdk::{contract, contractimpl, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Event as _, Vec};
+
+// ============================================================================
+// Mock GameHub for Unit Testing
+// ============================================================================
+
+#[contract]
+pub struct MockGameHub;
+
+#[contracttype]
+pub enum MockHubKey {
+    Players(u32),
+    Points(u32),
+    Player1Won(u32),
+    Starts(u32),
+}
+
+#[contractimpl]
+impl MockGameHub {
+    pub fn start_game(
+        env: Env,
+        _game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) {
+        // Remember what was reported so tests can check the hub integration.
+        env.storage().instance().set(&MockHubKey::Players(session_id), &(player1, player2));
+        env.storage().instance().set(&MockHubKey::Points(session_id), &(player1_points, player2_points));
+        let starts: u32 = env.storage().instance().get(&MockHubKey::Starts(session_id)).unwrap_or(0);
+        env.storage().instance().set(&MockHubKey::Starts(session_id), &(starts + 1));
+    }
+
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) {
+        env.storage().instance().set(&MockHubKey::Player1Won(session_id), &player1_won);
+    }
+
+    pub fn started_players(env: Env, session_id: u32) -> Option<(Address, Address)> {
+        env.storage().instance().get(&MockHubKey::Players(session_id))
+    }
+
+    pub fn started_points(env: Env, session_id: u32) -> Option<(i128, i128)> {
+        env.storage().instance().get(&MockHubKey::Points(session_id))
+    }
+
+    pub fn player1_won(env: Env, session_id: u32) -> Option<bool> {
+        env.storage().instance().get(&MockHubKey::Player1Won(session_id))
+    }
+
+    pub fn start_count(env: Env, session_id: u32) -> u32 {
+        env.storage().instance().get(&MockHubKey::Starts(session_id)).unwrap_or(0)
+    }
+}
+
+/// Death hook recording every (session_id, slot, role, cause) it hears about.
+#[contract]
+pub struct MockDeathHook;
+
+#[contractimpl]
+impl MockDeathHook {
+    pub fn on_death(env: Env, session_id: u32, slot: u32, role: u32, cause: u32) {
+        let mut deaths: Vec<(u32, u32, u32, u32)> = env.storage().instance().get(&0u32).unwrap_or(Vec::new(&env));
+        deaths.push_back((session_id, slot, role, cause));
+        env.storage().instance().set(&0u32, &deaths);
+    }
+
+    pub fn deaths(env: Env) -> Vec<(u32, u32, u32, u32)> {
+        env.storage().instance().get(&0u32).unwrap_or(Vec::new(&env))
+    }
+}
+
+/// Death hook that always traps.
+#[contract]
+pub struct BrokenDeathHook;
+
+#[contractimpl]
+impl BrokenDeathHook {
+    pub fn on_death(_env: Env, _session_id: u32, _slot: u32, _role: u32, _cause: u32) {
+        panic!("hook down");
+    }
+}
+
+/// Game hub that refuses every session, as when a player's points can't be locked.
+#[contract]
+pub struct RejectingGameHub;
+
+#[contractimpl]
+impl RejectingGameHub {
+    pub fn start_game(
+        _env: Env,
+        _game_id: Address,
+        _session_id: u32,
+        _player1: Address,
+        _player2: Address,
+        _player1_points: i128,
+        _player2_points: i128,
+    ) {
+        panic!("insufficient points");
+    }
+}
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn setup_test() -> (Env, MafiaDuelContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    env.ledger().set(soroban_sdk::testutils::LedgerInfo {
+        timestamp: 1441065600,
+        protocol_version: 25,
+        sequence_number: 100,
+        network_id: Default::default(),
+        base_reserve: 10,
+        min_temp_entry_ttl: u32::MAX / 2,
+        min_persistent_entry_ttl: u32::MAX / 2,
+        max_entry_ttl: u32::MAX / 2,
+    });
+
+    let hub_addr = env.register(MockGameHub, ());
+    let admin = Address::generate(&env);
+    let contract_id = env.register(MafiaDuelContract, (&admin, &hub_addr));
+    let client = MafiaDuelContractClient::new(&env, &contract_id);
+
+    (env, client, hub_addr)
+}
+
+/// Create a lobby and seat `humans` players (creator included). Returns their addresses by slot.
+fn seat_humans(env: &Env, client: &MafiaDuelContractClient, session_id: u32, humans: u32) -> Vec<Address> {
+    let mut players = Vec::new(env);
+    let creator = Address::generate(env);
+    client.create_game(&session_id, &creator, &100);
+    players.push_back(creator);
+    for _ in 1..humans {
+        let p = Address::generate(env);
+        client.join_game(&session_id, &p);
+        players.push_back(p);
+    }
+    players
+}
+
+fn default_config() -> GameConfig {
+    GameConfig { rules: 0, grace_ledgers: 0, tie_breaker: TIE_FIRST_SLOT, day_eliminations: 1, player_count: MAX_PLAYERS, consolation_bps: 0,
+        ability_charges: 1, recharge_every: 0, discussion_ledgers: 0, practice: false,
+        mafia_tie_breaker: MAFIA_TIE_RANDOM, stake_timing: STAKE_NONE }
+}
+
+fn configure(client: &MafiaDuelContractClient, session_id: u32, creator: &Address, rules: u32) {
+    client.configure_game(&session_id, creator, &GameConfig { rules, ..default_config() });
+}
+
+/// Seat `humans` players, configure `rules` and begin night one. Returns the env, client and seated players.
+fn begun_game(session_id: u32, humans: u32, rules: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    begun_game_with(session_id, humans, GameConfig { rules, ..default_config() })
+}
+
+/// [`begun_game`] under a full `config`.
+fn begun_game_with(session_id: u32, humans: u32, config: GameConfig) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, session_id, humans);
+    let creator = players.get(0).unwrap();
+    client.configure_game(&session_id, &creator, &config);
+    client.begin_game(&session_id, &creator);
+    (env, client, players)
+}
+
+fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|l| l.sequence_number += ledgers);
+}
+
+fn commitment(env: &Env, target: u32, nonce: u64) -> BytesN<32> {
+    let mut raw = [0u8; 12];
+    raw[0..4].copy_from_slice(&target.to_be_bytes());
+    raw[4..12].copy_from_slice(&nonce.to_be_bytes());
+    env.crypto().sha256(&Bytes::from_array(env, &raw)).into()
+}
+
+fn slots_with_role(client: &MafiaDuelContractClient, session_id: u32, role: u32) -> Vec<u32> {
+    let game = client.get_game(&session_id).unwrap();
+    let mut out = Vec::new(&client.env);
+    for i in 0..MAX_PLAYERS {
+        if game.slots.get(i).unwrap().role == role { out.push_back(i); }
+    }
+    out
+}
+
+/// Every living human commits to `targets[slot]` (nonce = slot), moving the game to reveal.
+fn commit_all(env: &Env, client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>, targets: &[u32; 8]) {
+    let game = client.get_game(&session_id).unwrap();
+    for i in 0..players.len() {
+        if !game.slots.get(i).unwrap().alive { continue; }
+        let c = commitment(env, targets[i as usize], i as u64);
+        client.submit_commitment(&session_id, &players.get(i).unwrap(), &c);
+    }
+}
+
+/// Living humans who haven't committed yet commit to PASS (nonce = slot).
+fn commit_all_remaining(env: &Env, client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>) {
+    let game = client.get_game(&session_id).unwrap();
+    for i in 0..players.len() {
+        let s = game.slots.get(i).unwrap();
+        if !s.alive || s.submitted { continue; }
+        client.submit_commitment(&session_id, &players.get(i).unwrap(), &commitment(env, PASS_TARGET, i as u64));
+    }
+}
+
+/// Every living human reveals the target they committed to in `commit_all`.
+fn reveal_all(client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>, targets: &[u32; 8]) {
+    let game = client.get_game(&session_id).unwrap();
+    for i in 0..players.len() {
+        if !game.slots.get(i).unwrap().alive { continue; }
+        client.reveal_action(&session_id, &players.get(i).unwrap(), &targets[i as usize], &(i as u64));
+    }
+}
+
+fn reveal_all_except(client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>, skip: u32) {
+    let game = client.get_game(&session_id).unwrap();
+    for i in 0..players.len() {
+        let s = game.slots.get(i).unwrap();
+        if i == skip || !s.alive || s.commitment.is_none() { continue; }
+        client.reveal_action(&session_id, &players.get(i).unwrap(), &PASS_TARGET, &(i as u64));
+    }
+}
+
+fn run_night(env: &Env, client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>, targets: &[u32; 8]) {
+    commit_all(env, client, session_id, players, targets);
+    reveal_all(client, session_id, players, targets);
+    client.resolve(&session_id);
+}
+
+/// Every living human casts `votes[slot]`, then the day resolves.
+fn run_day(client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>, votes: &[u32; 8]) {
+    let game = client.get_game(&session_id).unwrap();
+    for i in 0..players.len() {
+        if !game.slots.get(i).unwrap().alive { continue; }
+        client.submit_action(&session_id, &players.get(i).unwrap(), &votes[i as usize]);
+    }
+    client.resolve(&session_id);
+}
+
+/// Peaceful nights, and the town lynches each mafia in turn (8 humans).
+fn play_town_win(env: &Env, client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>) {
+    let mafia = slots_with_role(client, session_id, ROLE_MAFIA);
+    for m in mafia.iter() {
+        run_night(env, client, session_id, players, &[PASS_TARGET; 8]);
+        run_day(client, session_id, players, &[m; 8]);
+    }
+}
+
+/// Mafia kill the lowest living town slot each night; days pass (8 humans).
+fn play_mafia_win(env: &Env, client: &MafiaDuelContractClient, session_id: u32, players: &Vec<Address>) {
+    let mafia = slots_with_role(client, session_id, ROLE_MAFIA);
+    while client.get_game(&session_id).unwrap().phase != PHASE_OVER {
+        let game = client.get_game(&session_id).unwrap();
+        let mut victim = 0;
+        while mafia.contains(victim) || !game.slots.get(victim).unwrap().alive { victim += 1; }
+        let mut targets = [PASS_TARGET; 8];
+        for m in mafia.iter() { targets[m as usize] = victim; }
+        run_night(env, client, session_id, players, &targets);
+        if client.get_game(&session_id).unwrap().phase == PHASE_OVER { break; }
+        run_day(client, session_id, players, &[PASS_TARGET; 8]);
+    }
+}
+
+/// First living non-mafia slot, the default night victim in tests.
+fn first_town(client: &MafiaDuelContractClient, session_id: u32) -> u32 {
+    let game = client.get_game(&session_id).unwrap();
+    (0..MAX_PLAYERS)
+        .find(|&i| {
+            let s = game.slots.get(i).unwrap();
+            s.alive && s.role != ROLE_MAFIA
+        })
+        .unwrap()
+}
+
+/// Night targets where every mafia seat kills `victim` and everyone else passes.
+fn mafia_kill(client: &MafiaDuelContractClient, session_id: u32, victim: u32) -> [u32; 8] {
+    let mut targets = [PASS_TARGET; 8];
+    for m in slots_with_role(client, session_id, ROLE_MAFIA).iter() { targets[m as usize] = victim; }
+    targets
+}
+
+/// Assert that a Result contains a specific mafia_duel error
+fn assert_mafia_error<T, E>(
+    result: &Result<Result<T, E>, Result<MafiaError, soroban_sdk::InvokeError>>,
+    expected_error: MafiaError,
+) {
+    match result {
+        Err(Ok(actual_error)) => {
+            assert_eq!(
+                *actual_error, expected_error,
+                "Expected error {:?} (code {}), but got {:?} (code {})",
+                expected_error, expected_error as u32, actual_error, *actual_error as u32
+            );
+        }
+        Err(Err(_invoke_error)) => {
+            panic!(
+                "Expected contract error {:?} (code {}), but got invocation error",
+                expected_error, expected_error as u32
+            );
+        }
+        Ok(Err(_conv_error)) => {
+            panic!(
+                "Expected contract error {:?} (code {}), but got conversion error",
+                expected_error, expected_error as u32
+            );
+        }
+        Ok(Ok(_)) => {
+            panic!(
+                "Expected error {:?} (code {}), but operation succeeded",
+                expected_error, expected_error as u32
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Night Action Tests
+// ============================================================================
+
+#[test]
+fn test_mafia_cannot_kill_teammate() {
+    let session_id = 1u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let (m1, m2) = (mafia.get(0).unwrap(), mafia.get(1).unwrap());
+    let mut targets = [PASS_TARGET; 8];
+    targets[m1 as usize] = m2;
+    commit_all(&env, &client, session_id, &players, &targets);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+
+    let result = client.try_reveal_action(&session_id, &players.get(m1).unwrap(), &m2, &(m1 as u64));
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+}
+
+#[test]
+fn test_team_kill_rule_allows_mafia_on_mafia() {
+    let session_id = 2u32;
+    let (env, client, players) = begun_game(session_id, 8, RULE_TEAM_KILL);
+
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let (m1, m2) = (mafia.get(0).unwrap(), mafia.get(1).unwrap());
+    let mut targets = [PASS_TARGET; 8];
+    targets[m1 as usize] = m2;
+    commit_all(&env, &client, session_id, &players, &targets);
+
+    client.reveal_action(&session_id, &players.get(m1).unwrap(), &m2, &(m1 as u64));
+    let slot = client.get_game(&session_id).unwrap().slots.get(m1).unwrap();
+    assert_eq!(slot.action, Some(m2));
+}
+
+// ============================================================================
+// Post-Game Getter Tests
+// ============================================================================
+
+#[test]
+fn test_team_members_hidden_until_game_over() {
+    let session_id = 3u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+    assert_mafia_error(&client.try_get_team_members(&session_id, &TEAM_TOWN), MafiaError::WrongPhase);
+
+    play_town_win(&env, &client, session_id, &players);
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!(game.winner, Some(TEAM_TOWN));
+
+    let town = client.get_team_members(&session_id, &TEAM_TOWN);
+    assert_eq!(town.len(), 6);
+    for (slot, role, addr) in town.iter() {
+        assert_ne!(role, ROLE_MAFIA);
+        assert_eq!(addr, Some(players.get(slot).unwrap()));
+    }
+    let mafia = client.get_team_members(&session_id, &TEAM_MAFIA);
+    assert_eq!(mafia.len(), 2);
+    assert!(mafia.iter().all(|(_, role, _)| role == ROLE_MAFIA));
+}
+
+#[test]
+fn test_team_members_after_mafia_win() {
+    let session_id = 4u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+    play_mafia_win(&env, &client, session_id, &players);
+
+    assert_eq!(client.get_game(&session_id).unwrap().winner, Some(TEAM_MAFIA));
+    let mafia = client.get_team_members(&session_id, &TEAM_MAFIA);
+    assert_eq!(mafia.len(), 2);
+    assert_eq!(client.get_team_members(&session_id, &TEAM_TOWN).len(), 6);
+}
+
+// ============================================================================
+// Grace Period Tests
+// ============================================================================
+
+#[test]
+fn test_silent_committer_blocks_resolve_within_grace() {
+    let session_id = 5u32;
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { grace_ledgers: 10, ..default_config() });
+
+    // The first mafia seat never commits; everyone else passes.
+    let silent = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    for i in 0..8u32 {
+        if i == silent { continue; }
+        client.submit_commitment(&session_id, &players.get(i).unwrap(), &commitment(&env, PASS_TARGET, i as u64));
+    }
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_COMMIT);
+    advance_ledgers(&env, 9);
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::NotYetReady);
+
+    // Past grace the commit phase moves on, and the silent mafia's kill is chosen by the AI.
+    advance_ledgers(&env, 1);
+    client.resolve(&session_id);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+    reveal_all_except(&client, session_id, &players, silent);
+    client.resolve(&session_id);
+
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert!(game.last_killed.is_some());
+}
+
+#[test]
+fn test_silent_voter_handed_to_ai_after_grace() {
+    let session_id = 6u32;
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { grace_ledgers: 10, ..default_config() });
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+
+    // Seven humans pass the day vote; slot 7 stays silent.
+    for i in 0..7u32 {
+        client.submit_action(&session_id, &players.get(i).unwrap(), &PASS_TARGET);
+    }
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::NotYetReady);
+    advance_ledgers(&env, 10);
+    client.resolve(&session_id);
+
+    // The AI's lone vote takes the day.
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert_eq!(game.last_voted_out.len(), 1);
+    let out = game.last_voted_out.get(0).unwrap();
+    assert_ne!(out, 7);
+    assert!(!game.slots.get(out).unwrap().alive);
+}
+
+#[test]
+fn test_without_grace_silent_revealer_passes() {
+    let session_id = 7u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    commit_all(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    reveal_all_except(&client, session_id, &players, mafia.get(0).unwrap());
+    client.resolve(&session_id);
+    assert_eq!(client.get_game(&session_id).unwrap().last_killed, None);
+}
+
+// ============================================================================
+// Lobby Tests
+// ============================================================================
+
+#[test]
+fn test_create_and_join_seats_both_players() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 8u32;
+    let creator = Address::generate(&env);
+    let second = Address::generate(&env);
+    assert_eq!(client.create_and_join(&session_id, &creator, &second, &100, &default_config()), session_id);
+
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!(game.human_count, 2);
+    assert_eq!(game.slots.get(0).unwrap().addr, Some(creator.clone()));
+    assert_eq!(game.slots.get(1).unwrap().addr, Some(second.clone()));
+    assert!(game.slots.get(2).unwrap().addr.is_none());
+
+    let again = client.try_create_and_join(&session_id, &creator, &second, &100, &default_config());
+    assert_mafia_error(&again, MafiaError::SessionExists);
+}
+
+#[test]
+fn test_create_and_join_rejects_same_player_twice() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 9u32;
+    let creator = Address::generate(&env);
+    let result = client.try_create_and_join(&session_id, &creator, &creator, &100, &default_config());
+    assert_mafia_error(&result, MafiaError::AlreadyJoined);
+    assert!(client.get_game(&session_id).is_none());
+}
+
+// ============================================================================
+// Last Words Tests
+// ============================================================================
+
+#[test]
+fn test_killed_player_can_leave_last_words() {
+    let session_id = 10u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+
+    let victim = first_town(&client, session_id);
+    run_night(&env, &client, session_id, &players, &mafia_kill(&client, session_id, victim));
+    let words = Bytes::from_slice(&env, b"it was slot 3");
+    let result = client.try_leave_last_words(&session_id, &players.get(victim + 1).unwrap(), &words);
+    assert_mafia_error(&result, MafiaError::StillAlive);
+
+    let too_long = Bytes::from_array(&env, &[b'a'; MAX_LAST_WORDS as usize + 1]);
+    let result = client.try_leave_last_words(&session_id, &players.get(victim).unwrap(), &too_long);
+    assert_mafia_error(&result, MafiaError::TextTooLong);
+
+    client.leave_last_words(&session_id, &players.get(victim).unwrap(), &words);
+    let result = client.try_leave_last_words(&session_id, &players.get(victim).unwrap(), &words);
+    assert_mafia_error(&result, MafiaError::AlreadyActed);
+
+    let graveyard = client.get_graveyard(&session_id);
+    assert_eq!(graveyard.len(), 1);
+    let grave = graveyard.get(0).unwrap();
+    assert_eq!((grave.slot, grave.day, grave.cause), (victim, 1, DEATH_NIGHT_KILL));
+    assert_eq!(grave.last_words, Some(words));
+}
+
+#[test]
+fn test_last_words_window_closes_after_next_phase() {
+    let session_id = 11u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+
+    let lynched = first_town(&client, session_id);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    run_day(&client, session_id, &players, &[lynched; 8]);
+    let grave = client.get_graveyard(&session_id).get(0).unwrap();
+    assert_eq!(grave.cause, DEATH_LYNCH);
+
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    let words = Bytes::from_slice(&env, b"too late");
+    let result = client.try_leave_last_words(&session_id, &players.get(lynched).unwrap(), &words);
+    assert_mafia_error(&result, MafiaError::WrongPhase);
+}
+
+// ============================================================================
+// Player Stats Tests
+// ============================================================================
+
+#[test]
+fn test_stats_updated_at_game_end() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 12, 8);
+    let creator = players.get(0).unwrap();
+    assert_eq!(client.get_stats(&creator).games_played, 0);
+
+    client.begin_game(&12, &creator);
+    play_town_win(&env, &client, 12, &players);
+    let game = client.get_game(&12).unwrap();
+
+    for i in 0..8u32 {
+        let s = game.slots.get(i).unwrap();
+        let st = client.get_stats(&players.get(i).unwrap());
+        assert_eq!(st.games_played, 1);
+        assert_eq!(st.roles_played.get(s.role).unwrap(), 1);
+        assert_eq!(st.roles_played.iter().sum::<u32>(), 1);
+        assert_eq!(st.games_survived, s.alive as u32);
+        if s.role == ROLE_MAFIA {
+            assert_eq!((st.games_won, st.mafia_wins, st.town_wins), (0, 0, 0));
+        } else {
+            assert_eq!((st.games_won, st.mafia_wins, st.town_wins), (1, 0, 1));
+        }
+    }
+}
+
+#[test]
+fn test_stats_accumulate_across_games() {
+    let (env, client, _hub) = setup_test();
+    let mut players = seat_humans(&env, &client, 13, 8);
+    let creator = players.get(0).unwrap();
+    client.begin_game(&13, &creator);
+    play_mafia_win(&env, &client, 13, &players);
+    let first_role = client.get_game(&13).unwrap().slots.get(0).unwrap().role;
+
+    // Same creator hosts a second table.
+    client.create_game(&14, &creator, &100);
+    for i in 1..8u32 {
+        let p = Address::generate(&env);
+        client.join_game(&14, &p);
+        players.set(i, p);
+    }
+    client.begin_game(&14, &creator);
+    play_town_win(&env, &client, 14, &players);
+    let second_role = client.get_game(&14).unwrap().slots.get(0).unwrap().role;
+
+    let st = client.get_stats(&creator);
+    assert_eq!(st.games_played, 2);
+    let expected_wins = (first_role == ROLE_MAFIA) as u32 + (second_role != ROLE_MAFIA) as u32;
+    assert_eq!(st.games_won, expected_wins);
+    assert_eq!(st.games_won, st.mafia_wins + st.town_wins);
+    assert_eq!(st.roles_played.iter().sum::<u32>(), 2);
+}
+
+// ============================================================================
+// Claim Tests
+// ============================================================================
+
+#[test]
+fn test_conflicting_investigation_claims_are_stored() {
+    let session_id = 15u32;
+    let (env, client, players) = begun_game(session_id, 8, 0);
+    let result = client.try_claim_investigation(&session_id, &players.get(0).unwrap(), &1, &3, &true);
+    assert_mafia_error(&result, MafiaError::WrongPhase);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+
+    // Two players claim opposite results about slot 3; neither is verified.
+    client.claim_investigation(&session_id, &players.get(1).unwrap(), &1, &3, &true);
+    client.claim_investigation(&session_id, &players.get(2).unwrap(), &1, &3, &false);
+    let again = client.try_claim_investigation(&session_id, &players.get(1).unwrap(), &1, &4, &false);
+    assert_mafia_error(&again, MafiaError::AlreadyActed);
+    let future = client.try_claim_investigation(&session_id, &players.get(4).unwrap(), &2, &3, &false);
+    assert_mafia_error(&future, MafiaError::InvalidTarget);
+
+    let claims = client.get_claims(&session_id);
+    assert_eq!(claims.len(), 2);
+    let (a, b) = (claims.get(0).unwrap(), claims.get(1).unwrap());
+    assert_eq!((a.claimer, a.day, a.target, a.is_mafia), (1, 1, 3, true));
+    assert_eq!((b.claimer, b.day, b.target, b.is_mafia), (2, 1, 3, false));
+}
+
+// ============================================================================
+// Vigilante Tests
+// ============================================================================
+
+/// Vigilante shoots a plain town member while the doctor protects `doctor_protects_vig ? vig : nobody`.
+fn vigilante_backfire_night(rules: u32, doctor_protects_vig: bool) -> (MafiaDuelContractClient<'static>, u32, u32) {
+    let session_id = 16u32;
+    let (env, client, players) = begun_game(session_id, 8, RULE_VIGILANTE | rules);
+
+    let vig = slots_with_role(&client, session_id, ROLE_VIGILANTE).get(0).unwrap();
+    let doctor = slots_with_role(&client, session_id, ROLE_DOCTOR).get(0).unwrap();
+    let game = client.get_game(&session_id).unwrap();
+    let victim = (0..8u32)
+        .find(|&i| i != vig && i != doctor && game.slots.get(i).unwrap().role != ROLE_MAFIA)
+        .unwrap();
+    let mut targets = [PASS_TARGET; 8];
+    targets[vig as usize] = victim;
+    if doctor_protects_vig { targets[doctor as usize] = vig; }
+    run_night(&env, &client, session_id, &players, &targets);
+    (client, vig, victim)
+}
+
+#[test]
+fn test_vigilante_backfires_on_town_kill() {
+    let (client, vig, victim) = vigilante_backfire_night(0, false);
+    let game = client.get_game(&16).unwrap();
+    assert!(!game.slots.get(victim).unwrap().alive);
+    assert!(!game.slots.get(vig).unwrap().alive);
+    let graveyard = client.get_graveyard(&16);
+    assert_eq!(graveyard.get(0).unwrap().cause, DEATH_VIGILANTE);
+    assert_eq!(graveyard.get(1).unwrap().cause, DEATH_BACKFIRE);
+}
+
+#[test]
+fn test_doctor_does_not_stop_backfire_by_default() {
+    let (client, vig, _victim) = vigilante_backfire_night(0, true);
+    assert!(!client.get_game(&16).unwrap().slots.get(vig).unwrap().alive);
+}
+
+#[test]
+fn test_doctor_stops_backfire_when_savable() {
+    let (client, vig, victim) = vigilante_backfire_night(RULE_VIG_BACKFIRE_SAVABLE, true);
+    let game = client.get_game(&16).unwrap();
+    assert!(game.slots.get(vig).unwrap().alive);
+    assert!(!game.slots.get(victim).unwrap().alive);
+    assert_eq!(client.get_graveyard(&16).len(), 1);
+}
+
+#[test]
+fn test_vigilante_shooting_mafia_has_no_backfire() {
+    let session_id = 17u32;
+    let (env, client, players) = begun_game(session_id, 8, RULE_VIGILANTE);
+
+    let vig = slots_with_role(&client, session_id, ROLE_VIGILANTE).get(0).unwrap();
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    let mut targets = [PASS_TARGET; 8];
+    targets[vig as usize] = mafia;
+    run_night(&env, &client, session_id, &players, &targets);
+
+    let game = client.get_game(&session_id).unwrap();
+    assert!(game.slots.get(vig).unwrap().alive);
+    assert!(!game.slots.get(mafia).unwrap().alive);
+    assert_eq!(client.get_graveyard(&session_id).get(0).unwrap().cause, DEATH_VIGILANTE);
+}
+
+// ============================================================================
+// Submission Status Tests
+// ============================================================================
+
+#[test]
+fn test_expected_submitters_tracks_each_phase() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 18u32;
+    let players = seat_humans(&env, &client, session_id, 3);
+    assert_eq!(client.expected_submitters(&session_id).len(), 0);
+    client.begin_game(&session_id, &players.get(0).unwrap());
+
+    // Only humans (slots 0..3) are ever expected; AI seats never block.
+    let expected = client.expected_submitters(&session_id);
+    assert_eq!(expected, Vec::from_array(&env, [0u32, 1, 2]));
+    client.submit_commitment(&session_id, &players.get(1).unwrap(), &commitment(&env, PASS_TARGET, 1));
+    assert_eq!(client.expected_submitters(&session_id), Vec::from_array(&env, [0u32, 2]));
+
+    commit_all_remaining(&env, &client, session_id, &players);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+    assert_eq!(client.expected_submitters(&session_id), Vec::from_array(&env, [0u32, 1, 2]));
+    client.reveal_action(&session_id, &players.get(0).unwrap(), &PASS_TARGET, &0);
+    assert_eq!(client.expected_submitters(&session_id), Vec::from_array(&env, [1u32, 2]));
+}
+
+// ============================================================================
+// Day Tie-Break Tests
+// ============================================================================
+
+/// Day 1 with the vote split 4-4 between slots `a` and `b` under the runoff tie breaker.
+fn open_runoff(session_id: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>, u32, u32) {
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { tie_breaker: TIE_RUNOFF, ..default_config() });
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+
+    let (a, b) = (0u32, 1u32);
+    run_day(&client, session_id, &players, &[b, a, a, a, a, b, b, b]);
+    (env, client, players, a, b)
+}
+
+#[test]
+fn test_runoff_restricts_ballot_and_resolves() {
+    let (env, client, players, a, b) = open_runoff(19);
+    let game = client.get_game(&19).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.day, 1);
+    assert_eq!(game.runoff_candidates, Vec::from_array(&env, [a, b]));
+    assert!(game.slots.iter().all(|s| s.alive && !s.submitted));
+
+    let off_ballot = client.try_submit_action(&19, &players.get(2).unwrap(), &5);
+    assert_mafia_error(&off_ballot, MafiaError::InvalidTarget);
+
+    run_day(&client, 19, &players, &[b, a, a, a, a, a, b, b]);
+    let game = client.get_game(&19).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert_eq!(game.last_voted_out, Vec::from_array(&env, [a]));
+    assert!(!game.slots.get(a).unwrap().alive);
+    assert!(game.runoff_candidates.is_empty());
+}
+
+#[test]
+fn test_tied_runoff_eliminates_no_one() {
+    let (_env, client, players, a, b) = open_runoff(20);
+    run_day(&client, 20, &players, &[b, a, a, a, a, b, b, b]);
+    let game = client.get_game(&20).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert_eq!(game.day, 2);
+    assert!(game.last_voted_out.is_empty());
+    assert!(game.slots.iter().all(|s| s.alive));
+}
+
+#[test]
+fn test_default_tie_eliminates_lowest_slot() {
+    let (env, client, players) = begun_game(21, 8, 0);
+    run_night(&env, &client, 21, &players, &[PASS_TARGET; 8]);
+    run_day(&client, 21, &players, &[1, 0, 0, 0, 0, 1, 1, 1]);
+    assert_eq!(client.get_game(&21).unwrap().last_voted_out, Vec::from_array(&env, [0u32]));
+}
+
+// ============================================================================
+// Watcher Tests
+// ============================================================================
+
+#[test]
+fn test_watcher_peek_reports_and_is_one_shot() {
+    let session_id = 22u32;
+    let (env, client, players) = begun_game(session_id, 8, RULE_WATCHER);
+
+    let villagers = slots_with_role(&client, session_id, ROLE_VILLAGER);
+    let (w1, w2) = (villagers.get(0).unwrap(), villagers.get(1).unwrap());
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let victim = slots_with_role(&client, session_id, ROLE_DOCTOR).get(0).unwrap();
+    let mut targets = mafia_kill(&client, session_id, victim);
+    targets[w1 as usize] = mafia.get(0).unwrap();
+    targets[w2 as usize] = villagers.get(2).unwrap();
+    run_night(&env, &client, session_id, &players, &targets);
+
+    let peek1 = client.get_peek_result(&session_id, &players.get(w1).unwrap());
+    assert_eq!(peek1, Some(Peek { target: mafia.get(0).unwrap(), acted: true }));
+    let peek2 = client.get_peek_result(&session_id, &players.get(w2).unwrap());
+    assert_eq!(peek2, Some(Peek { target: villagers.get(2).unwrap(), acted: false }));
+    assert_eq!(client.get_peek_result(&session_id, &players.get(villagers.get(3).unwrap()).unwrap()), None);
+
+    // Second night: the spent watcher can still commit, but a real peek is rejected at reveal.
+    run_day(&client, session_id, &players, &[PASS_TARGET; 8]);
+    let mut targets = [PASS_TARGET; 8];
+    targets[w1 as usize] = mafia.get(1).unwrap();
+    commit_all(&env, &client, session_id, &players, &targets);
+    let result = client.try_reveal_action(&session_id, &players.get(w1).unwrap(), &targets[w1 as usize], &(w1 as u64));
+    assert_mafia_error(&result, MafiaError::AbilityUsed);
+}
+
+// ============================================================================
+// Vote Tally Tests
+// ============================================================================
+
+#[test]
+fn test_votes_for_highest_slot_tally_correctly() {
+    let (env, client, players) = begun_game(23, 8, 0);
+    run_night(&env, &client, 23, &players, &[PASS_TARGET; 8]);
+
+    let top = MAX_PLAYERS - 1;
+    run_day(&client, 23, &players, &[top, top, top, top - 1, top - 1, top, top - 1, top]);
+    let game = client.get_game(&23).unwrap();
+    assert_eq!(game.last_voted_out, Vec::from_array(&env, [top]));
+    assert!(!game.slots.get(top).unwrap().alive);
+
+    let result = client.try_submit_action(&23, &players.get(0).unwrap(), &MAX_PLAYERS);
+    assert_mafia_error(&result, MafiaError::WrongPhase);
+    run_night(&env, &client, 23, &players, &[PASS_TARGET; 8]);
+    let result = client.try_submit_action(&23, &players.get(0).unwrap(), &MAX_PLAYERS);
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+}
+
+// ============================================================================
+// Archive Tests
+// ============================================================================
+
+fn stored_tiers(env: &Env, client: &MafiaDuelContractClient, session_id: u32) -> (bool, bool) {
+    env.as_contract(&client.address, || {
+        let key = DataKey::Game(session_id);
+        (env.storage().temporary().has(&key), env.storage().persistent().has(&key))
+    })
+}
+
+#[test]
+fn test_archive_and_restore_round_trip() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 24u32;
+    let players = seat_humans(&env, &client, session_id, 8);
+    let creator = players.get(0).unwrap();
+    client.begin_game(&session_id, &creator);
+
+    let outsider = Address::generate(&env);
+    assert_mafia_error(&client.try_archive_game(&session_id, &outsider), MafiaError::NotCreator);
+    client.archive_game(&session_id, &creator);
+    assert_eq!(stored_tiers(&env, &client, session_id), (false, true));
+    assert_mafia_error(&client.try_archive_game(&session_id, &creator), MafiaError::GameNotFound);
+
+    // Play continues against the archived copy.
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_DAY);
+    assert_eq!(stored_tiers(&env, &client, session_id), (false, true));
+    let dup = client.try_create_game(&session_id, &outsider, &100);
+    assert_mafia_error(&dup, MafiaError::SessionExists);
+
+    client.restore_game(&session_id, &client.get_admin());
+    assert_eq!(stored_tiers(&env, &client, session_id), (true, false));
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!((game.phase, game.day), (PHASE_DAY, 1));
+}
+
+#[test]
+fn test_side_entries_follow_the_game() {
+    let (env, client, players) = begun_game(243, 8, RULE_CONFIDANT);
+    let creator = players.get(0).unwrap();
+    let confidant = players.get(slots_with_role(&client, 243, ROLE_CONFIDANT).get(0).unwrap()).unwrap();
+    let intel = client.get_confidant_intel(&243, &confidant);
+    client.set_sealed_note(&243, &creator, &Bytes::from_slice(&env, b"note"));
+    let tiers = |key: DataKey| env.as_contract(&client.address, || (env.storage().temporary().has(&key), env.storage().persistent().has(&key)));
+
+    // Writing the game keeps the intel from begin_game alive with it.
+    advance_ledgers(&env, GAME_TTL_LEDGERS - 10);
+    run_night(&env, &client, 243, &players, &[PASS_TARGET; 8]);
+    advance_ledgers(&env, 20);
+    assert_eq!(client.get_confidant_intel(&243, &confidant), intel);
+
+    client.archive_game(&243, &creator);
+    assert_eq!(tiers(DataKey::ConfidantIntel(243)), (false, true));
+    assert_eq!(tiers(DataKey::SealedNote(243, 0)), (false, true));
+    assert_eq!(client.get_confidant_intel(&243, &confidant), intel);
+    client.restore_game(&243, &creator);
+    assert_eq!(tiers(DataKey::ConfidantIntel(243)), (true, false));
+    assert_eq!(tiers(DataKey::SealedNote(243, 0)), (true, false));
+}
+
+// ============================================================================
+// Derived Nonce Tests
+// ============================================================================
+
+#[test]
+fn test_derived_nonce_vector() {
+    let session_id = 25u32;
+    let (env, client, players) = begun_game(session_id, 1, 0);
+
+    let msg = client.nonce_message(&session_id, &1, &PHASE_NIGHT_COMMIT);
+    let mut expected = Bytes::from_slice(&env, b"mafia-duel:nonce");
+    expected.extend_from_array(&[0, 0, 0, 25, 0, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(msg, expected);
+
+    // Stand-in for the player's 64-byte signature over `msg`.
+    let signature = Bytes::from_array(&env, &[7u8; 64]);
+    let digest: BytesN<32> = env.crypto().sha256(&signature).into();
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&digest.to_array()[0..8]);
+    let nonce = u64::from_be_bytes(head);
+    assert_eq!(nonce, 0x6cfe_eb3a_a25d_3f41);
+
+    let player = players.get(0).unwrap();
+    client.submit_commitment(&session_id, &player, &commitment(&env, 3, nonce));
+    assert!(client.verify_derived_commitment(&session_id, &player, &3, &nonce));
+    assert!(!client.verify_derived_commitment(&session_id, &player, &3, &(nonce + 1)));
+    assert!(!client.verify_derived_commitment(&session_id, &player, &4, &nonce));
+}
+
+// ============================================================================
+// Deadline Extension Tests
+// ============================================================================
+
+#[test]
+fn test_extend_deadline_delays_forced_resolve() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 26u32;
+    let players = seat_humans(&env, &client, session_id, 2);
+    let creator = players.get(0).unwrap();
+    assert_mafia_error(&client.try_extend_deadline(&session_id, &creator, &5), MafiaError::WrongPhase);
+    client.configure_game(&session_id, &creator, &GameConfig { grace_ledgers: 10, ..default_config() });
+    client.begin_game(&session_id, &creator);
+    assert_eq!(client.get_phase_deadline(&session_id), Some(110));
+
+    // Slot 1 never commits.
+    client.submit_commitment(&session_id, &creator, &commitment(&env, PASS_TARGET, 0));
+    let joiner = players.get(1).unwrap();
+    assert_mafia_error(&client.try_extend_deadline(&session_id, &joiner, &5), MafiaError::NotCreator);
+    assert_eq!(client.extend_deadline(&session_id, &creator, &5), 115);
+    let event = DeadlineExtended { session_id, deadline_ledger: 115 };
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [event.to_xdr(&env, &client.address)]
+    );
+
+    advance_ledgers(&env, 10);
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::NotYetReady);
+    advance_ledgers(&env, 5);
+    client.resolve(&session_id);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+
+    // The extension belonged to the commit phase; the reveal phase starts fresh.
+    assert_eq!(client.get_game(&session_id).unwrap().deadline_extension, 0);
+    let too_far = client.try_extend_deadline(&session_id, &creator, &(MAX_DEADLINE_EXTENSION + 1));
+    assert_mafia_error(&too_far, MafiaError::LimitExceeded);
+}
+
+#[test]
+fn test_role_action_hints() {
+    let (env, client, _hub) = setup_test();
+    assert_eq!(client.role_action_hint(&ROLE_SHERIFF), Bytes::from_slice(&env, b"Choose a player to investigate"));
+    for role in 0..ROLE_COUNT {
+        let hint = client.role_action_hint(&role);
+        assert!(!hint.is_empty() && hint.len() <= 80);
+    }
+    assert!(client.role_action_hint(&ROLE_COUNT).is_empty());
+}
+
+#[test]
+fn test_supported_roles_match_role_set() {
+    let (_env, client, _hub) = setup_test();
+    let roles = client.supported_roles();
+    assert_eq!(roles.len(), ROLE_COUNT);
+    for (i, role) in roles.iter().enumerate() {
+        assert_eq!(role, i as u32);
+        assert!(!client.role_action_hint(&role).is_empty());
+    }
+    assert!(roles.contains(ROLE_CONFIDANT));
+    assert!(!roles.contains(ROLE_NONE));
+}
+
+// ============================================================================
+// Double Lynch Tests
+// ============================================================================
+
+fn double_lynch_day(session_id: u32, tie_breaker: u32, votes: &[u32; 8]) -> (Env, MafiaDuelContractClient<'static>) {
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { day_eliminations: 2, tie_breaker, ..default_config() });
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    run_day(&client, session_id, &players, votes);
+    (env, client)
+}
+
+#[test]
+fn test_double_lynch_eliminates_top_two() {
+    let (env, client) = double_lynch_day(27, TIE_FIRST_SLOT, &[2, 2, 5, 5, 5, 2, 6, 6]);
+    let game = client.get_game(&27).unwrap();
+    assert_eq!(game.last_voted_out, Vec::from_array(&env, [2u32, 5]));
+    assert!(!game.slots.get(2).unwrap().alive && !game.slots.get(5).unwrap().alive);
+    assert_eq!(client.get_graveyard(&27).len(), 2);
+}
+
+#[test]
+fn test_double_lynch_tie_at_cutoff() {
+    // Slot 3 leads; slots 1 and 6 tie for the second elimination.
+    let votes = [3, 3, 3, 3, 1, 1, 6, 6];
+    let (env, client) = double_lynch_day(28, TIE_FIRST_SLOT, &votes);
+    assert_eq!(client.get_game(&28).unwrap().last_voted_out, Vec::from_array(&env, [3u32, 1]));
+
+    let (env, client) = double_lynch_day(29, TIE_NO_ELIM, &votes);
+    let game = client.get_game(&29).unwrap();
+    assert_eq!(game.last_voted_out, Vec::from_array(&env, [3u32]));
+    assert!(game.slots.get(1).unwrap().alive && game.slots.get(6).unwrap().alive);
+}
+
+#[test]
+fn test_invalid_day_eliminations_rejected() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 30, 1);
+    let config = GameConfig { day_eliminations: 0, ..default_config() };
+    let result = client.try_configure_game(&30, &players.get(0).unwrap(), &config);
+    assert_mafia_error(&result, MafiaError::InvalidConfig);
+}
+
+// ============================================================================
+// Target Preflight Tests
+// ============================================================================
+
+#[test]
+fn test_validate_intended_target_legal_and_illegal() {
+    let session_id = 31u32;
+    let (_env, client, players) = begun_game(session_id, 8, 0);
+
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let (m1, m2) = (mafia.get(0).unwrap(), mafia.get(1).unwrap());
+    let mafia_addr = players.get(m1).unwrap();
+    let town = first_town(&client, session_id);
+
+    client.validate_intended_target(&session_id, &mafia_addr, &town);
+    client.validate_intended_target(&session_id, &mafia_addr, &PASS_TARGET);
+    let result = client.try_validate_intended_target(&session_id, &mafia_addr, &m1);
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+    let result = client.try_validate_intended_target(&session_id, &mafia_addr, &m2);
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+    let result = client.try_validate_intended_target(&session_id, &mafia_addr, &MAX_PLAYERS);
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+
+    // Doctors may self-save.
+    let doctor = slots_with_role(&client, session_id, ROLE_DOCTOR).get(0).unwrap();
+    client.validate_intended_target(&session_id, &players.get(doctor).unwrap(), &doctor);
+}
+
+#[test]
+fn test_validate_intended_target_outside_night() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 32u32;
+    let players = seat_humans(&env, &client, session_id, 8);
+    let result = client.try_validate_intended_target(&session_id, &players.get(0).unwrap(), &1);
+    assert_mafia_error(&result, MafiaError::WrongPhase);
+}
+
+// ============================================================================
+// Merkle Commitment Tests
+// ============================================================================
+
+fn hash_pair(env: &Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+    let mut buf = Bytes::new(env);
+    buf.append(&left.clone().into());
+    buf.append(&right.clone().into());
+    env.crypto().sha256(&buf).into()
+}
+
+/// sha256(component || target || nonce), the Merkle leaf for one action component.
+fn component_leaf(env: &Env, component: u32, target: u32, nonce: u64) -> BytesN<32> {
+    let mut raw = [0u8; 16];
+    raw[0..4].copy_from_slice(&component.to_be_bytes());
+    raw[4..8].copy_from_slice(&target.to_be_bytes());
+    raw[8..16].copy_from_slice(&nonce.to_be_bytes());
+    env.crypto().sha256(&Bytes::from_array(env, &raw)).into()
+}
+
+/// Seat eight humans, have the first mafia commit a two-leaf root, and everyone else a plain hash.
+fn merkle_night(session_id: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>, u32, u32, BytesN<32>, BytesN<32>) {
+    let (env, client, players) = begun_game(session_id, 8, 0);
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    let town = first_town(&client, session_id);
+
+    let kill_leaf = component_leaf(&env, ACTION_COMPONENT, town, 77);
+    let other_leaf = component_leaf(&env, ACTION_COMPONENT + 1, PASS_TARGET, 78);
+    let root = hash_pair(&env, &kill_leaf, &other_leaf);
+    client.submit_commitment(&session_id, &players.get(mafia).unwrap(), &root);
+    commit_all_remaining(&env, &client, session_id, &players);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+    (env, client, players, mafia, town, kill_leaf, other_leaf)
+}
+
+#[test]
+fn test_merkle_reveal_with_valid_path() {
+    let (env, client, players, mafia, town, _kill_leaf, other_leaf) = merkle_night(33);
+    let path = Vec::from_array(&env, [other_leaf]);
+    client.reveal_action_merkle(&33, &players.get(mafia).unwrap(), &town, &77, &path);
+    assert_eq!(client.get_game(&33).unwrap().slots.get(mafia).unwrap().action, Some(town));
+
+    reveal_all_except(&client, 33, &players, mafia);
+    client.resolve(&33);
+    assert!(!client.get_game(&33).unwrap().slots.get(town).unwrap().alive);
+}
+
+#[test]
+fn test_merkle_reveal_rejects_tampered_path() {
+    let (env, client, players, mafia, town, kill_leaf, _other_leaf) = merkle_night(34);
+    let addr = players.get(mafia).unwrap();
+
+    let forged = Vec::from_array(&env, [commitment(&env, PASS_TARGET, 1)]);
+    let result = client.try_reveal_action_merkle(&34, &addr, &town, &77, &forged);
+    assert_mafia_error(&result, MafiaError::InvalidReveal);
+    assert_mafia_error(&client.try_reveal_action_merkle(&34, &addr, &town, &77, &Vec::new(&env)), MafiaError::InvalidReveal);
+
+    // The plain reveal cannot open a root commitment either.
+    let result = client.try_reveal_action(&34, &addr, &town, &77);
+    assert_mafia_error(&result, MafiaError::InvalidReveal);
+
+    let mut too_deep = Vec::new(&env);
+    for _ in 0..=MAX_MERKLE_DEPTH { too_deep.push_back(kill_leaf.clone()); }
+    let result = client.try_reveal_action_merkle(&34, &addr, &town, &77, &too_deep);
+    assert_mafia_error(&result, MafiaError::LimitExceeded);
+}
+
+#[test]
+fn test_merkle_reveal_rejects_other_leaf_of_same_root() {
+    let (env, client, players, mafia, _town, kill_leaf, _other_leaf) = merkle_night(226);
+    let addr = players.get(mafia).unwrap();
+    // The second leaf is a well-formed pass under the same root, but it sits at component 1.
+    let path = Vec::from_array(&env, [kill_leaf]);
+    let result = client.try_reveal_action_merkle(&226, &addr, &PASS_TARGET, &78, &path);
+    assert_mafia_error(&result, MafiaError::InvalidReveal);
+    assert!(!client.get_game(&226).unwrap().slots.get(mafia).unwrap().submitted);
+}
+
+// ============================================================================
+// Serial Killer / Multi-Faction Win Tests
+// ============================================================================
+
+/// Mark `victims` dead directly in storage to set up an endgame board.
+fn force_dead(env: &Env, client: &MafiaDuelContractClient, session_id: u32, victims: &Vec<u32>) {
+    let mut game = client.get_game(&session_id).unwrap();
+    for v in victims.iter() {
+        let mut s = game.slots.get(v).unwrap();
+        s.alive = false;
+        game.slots.set(v, s);
+    }
+    env.as_contract(&client.address, || {
+        env.storage().temporary().set(&DataKey::Game(session_id), &game);
+    });
+}
+
+/// Start an eight-human serial-killer game and kill town players until only `town_left` remain.
+fn serial_killer_endgame(session_id: u32, rules: u32, town_left: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    let (env, client, players) = begun_game(session_id, 8, RULE_SERIAL_KILLER | rules);
+    assert_eq!(slots_with_role(&client, session_id, ROLE_SERIAL_KILLER).len(), 1);
+
+    let game = client.get_game(&session_id).unwrap();
+    let mut town = Vec::new(&env);
+    for i in 0..MAX_PLAYERS {
+        if game.slots.get(i).unwrap().role != ROLE_MAFIA && game.slots.get(i).unwrap().role != ROLE_SERIAL_KILLER {
+            town.push_back(i);
+        }
+    }
+    force_dead(&env, &client, session_id, &town.slice(town_left..));
+    (env, client, players)
+}
+
+#[test]
+fn test_serial_killer_blocks_mafia_parity_win() {
+    // Two mafia against one town and the serial killer: parity counting everyone else.
+    let (env, client, players) = serial_killer_endgame(35, RULE_NEUTRAL_BLOCKS_PARITY, 1);
+    run_night(&env, &client, 35, &players, &[PASS_TARGET; 8]);
+    let game = client.get_game(&35).unwrap();
+    assert_eq!(game.winner, None);
+    assert_eq!(game.phase, PHASE_DAY);
+
+    let (env, client, players) = serial_killer_endgame(36, 0, 1);
+    run_night(&env, &client, 36, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&36).unwrap().winner, Some(TEAM_MAFIA));
+}
+
+#[test]
+fn test_serial_killer_wins_one_on_one() {
+    let (env, client, players) = serial_killer_endgame(37, RULE_NEUTRAL_BLOCKS_PARITY, 0);
+    let mafia = slots_with_role(&client, 37, ROLE_MAFIA);
+    let sk = slots_with_role(&client, 37, ROLE_SERIAL_KILLER).get(0).unwrap();
+
+    // The serial killer takes one mafia at night, leaving a one-on-one.
+    let mut targets = [PASS_TARGET; 8];
+    targets[sk as usize] = mafia.get(0).unwrap();
+    run_night(&env, &client, 37, &players, &targets);
+    let game = client.get_game(&37).unwrap();
+    assert_eq!(game.graveyard.last().unwrap().cause, DEATH_SERIAL_KILL);
+    assert_eq!(game.winner, Some(TEAM_SERIAL_KILLER));
+    assert_eq!(game.phase, PHASE_OVER);
+}
+
+#[test]
+fn test_town_wins_only_after_serial_killer_dies() {
+    let (env, client, players) = serial_killer_endgame(38, 0, 3);
+    let mafia = slots_with_role(&client, 38, ROLE_MAFIA);
+    force_dead(&env, &client, 38, &mafia);
+    run_night(&env, &client, 38, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&38).unwrap().winner, None);
+
+    let sk = slots_with_role(&client, 38, ROLE_SERIAL_KILLER).get(0).unwrap();
+    run_day(&client, 38, &players, &[sk; 8]);
+    let game = client.get_game(&38).unwrap();
+    assert_eq!(game.winner, Some(TEAM_TOWN));
+}
+
+// ============================================================================
+// Hub Integration Tests
+// ============================================================================
+
+#[test]
+fn test_begin_game_reports_first_two_humans_to_hub() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = seat_humans(&env, &client, 39, 3);
+    client.begin_game(&39, &players.get(0).unwrap());
+    assert_eq!(hub_client.started_players(&39), Some((players.get(0).unwrap(), players.get(1).unwrap())));
+}
+
+#[test]
+fn test_begin_game_solo_creator_reported_twice() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = seat_humans(&env, &client, 40, 1);
+    let creator = players.get(0).unwrap();
+    client.begin_game(&40, &creator);
+    assert_eq!(hub_client.started_players(&40), Some((creator.clone(), creator)));
+}
+
+// ============================================================================
+// Partial Reveal Tests
+// ============================================================================
+
+/// Grace game where the mafia go for a villager, the doctor protects that villager and the
+/// sheriff checks a mafia. Only seats whose role is in `revealing` open their commitment before
+/// the reveal grace runs out.
+fn partial_reveal_night(session_id: u32, revealing: &[u32]) -> (MafiaDuelContractClient<'static>, u32) {
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { grace_ledgers: 10, ..default_config() });
+
+    let victim = slots_with_role(&client, session_id, ROLE_VILLAGER).get(0).unwrap();
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    let game = client.get_game(&session_id).unwrap();
+    let mut targets = [PASS_TARGET; 8];
+    for i in 0..MAX_PLAYERS {
+        targets[i as usize] = match game.slots.get(i).unwrap().role {
+            ROLE_MAFIA | ROLE_DOCTOR => victim,
+            ROLE_SHERIFF             => mafia,
+            _                        => PASS_TARGET,
+        };
+    }
+    commit_all(&env, &client, session_id, &players, &targets);
+    for i in 0..MAX_PLAYERS {
+        let role = game.slots.get(i).unwrap().role;
+        if role == ROLE_VILLAGER || revealing.contains(&role) {
+            client.reveal_action(&session_id, &players.get(i).unwrap(), &targets[i as usize], &(i as u64));
+        }
+    }
+    advance_ledgers(&env, 10);
+    client.resolve(&session_id);
+    (client, victim)
+}
+
+#[test]
+fn test_only_mafia_revealed_kill_lands_unsaved() {
+    let (client, victim) = partial_reveal_night(41, &[ROLE_MAFIA]);
+    let game = client.get_game(&41).unwrap();
+    assert_eq!(game.last_killed, Some(victim));
+    assert!(!game.last_saved);
+    assert!(!game.slots.get(victim).unwrap().alive);
+    // The unrevealed sheriff is not replaced by an AI investigation.
+    assert_eq!(game.last_investigated, None);
+}
+
+#[test]
+fn test_only_doctor_revealed_no_substitute_kill() {
+    let (client, victim) = partial_reveal_night(42, &[ROLE_DOCTOR]);
+    let game = client.get_game(&42).unwrap();
+    assert_eq!(game.last_killed, None);
+    assert!(!game.last_saved);
+    assert_eq!(game.last_investigated, None);
+    assert!(game.slots.get(victim).unwrap().alive);
+}
+
+#[test]
+fn test_none_revealed_night_is_quiet() {
+    let (client, _victim) = partial_reveal_night(43, &[]);
+    let game = client.get_game(&43).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.last_killed, None);
+    assert_eq!(game.last_investigated, None);
+    assert!(game.graveyard.is_empty());
+}
+
+// ============================================================================
+// Decision Space Tests
+// ============================================================================
+
+#[test]
+fn test_decision_space_night_roles() {
+    let (env, client, players) = begun_game(44, 8, RULE_WATCHER);
+
+    let mafia = slots_with_role(&client, 44, ROLE_MAFIA);
+    let m1 = mafia.get(0).unwrap();
+    let space = client.decision_space(&44, &players.get(m1).unwrap());
+    assert_eq!(space.role, ROLE_MAFIA);
+    assert_eq!(space.phase, PHASE_NIGHT_COMMIT);
+    assert!(space.can_pass);
+    assert_eq!(space.legal_targets.len(), 6);
+    for m in mafia.iter() { assert!(!space.legal_targets.contains(m)); }
+    assert_eq!(space.abilities_left, 0);
+
+    let doctor = slots_with_role(&client, 44, ROLE_DOCTOR).get(0).unwrap();
+    assert_eq!(client.decision_space(&44, &players.get(doctor).unwrap()).legal_targets.len(), 8);
+
+    let villager = slots_with_role(&client, 44, ROLE_VILLAGER).get(0).unwrap();
+    let space = client.decision_space(&44, &players.get(villager).unwrap());
+    assert_eq!(space.abilities_left, 1);
+    assert_eq!(space.legal_targets.len(), 8);
+
+    // Once committed, nothing is left to decide this phase.
+    client.submit_commitment(&44, &players.get(m1).unwrap(), &commitment(&env, PASS_TARGET, 0));
+    let space = client.decision_space(&44, &players.get(m1).unwrap());
+    assert!(space.legal_targets.is_empty() && !space.can_pass);
+}
+
+#[test]
+fn test_decision_space_day_and_lobby() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 45, 8);
+    let creator = players.get(0).unwrap();
+    let lobby = client.decision_space(&45, &creator);
+    assert_eq!(lobby.phase, PHASE_LOBBY);
+    assert!(lobby.legal_targets.is_empty() && !lobby.can_pass);
+
+    client.begin_game(&45, &creator);
+    let victim = mafia_kill(&client, 45, first_town(&client, 45));
+    run_night(&env, &client, 45, &players, &victim);
+    let dead = client.get_game(&45).unwrap().last_killed.unwrap();
+    let alive = (0..MAX_PLAYERS).find(|&i| i != dead).unwrap();
+
+    let space = client.decision_space(&45, &players.get(alive).unwrap());
+    assert_eq!(space.phase, PHASE_DAY);
+    assert_eq!(space.legal_targets.len(), 7);
+    assert!(!space.legal_targets.contains(dead));
+    assert!(client.decision_space(&45, &players.get(dead).unwrap()).legal_targets.is_empty());
+}
+
+// ============================================================================
+// Human-less Endgame Tests
+// ============================================================================
+
+#[test]
+fn test_auto_play_after_all_humans_die() {
+    let (env, client, _) = begun_game(46, 2, 0);
+    assert_mafia_error(&client.try_auto_play_remaining(&46), MafiaError::StillAlive);
+
+    force_dead(&env, &client, 46, &Vec::from_array(&env, [0u32, 1]));
+    let winner = client.auto_play_remaining(&46);
+    let game = client.get_game(&46).unwrap();
+    assert!(winner.is_some());
+    assert_eq!(game.winner, winner);
+    assert_eq!(game.phase, PHASE_OVER);
+    assert_mafia_error(&client.try_auto_play_remaining(&46), MafiaError::GameAlreadyOver);
+}
+
+#[test]
+fn test_resolve_moves_humanless_commit_phase() {
+    let (env, client, _) = begun_game(47, 1, 0);
+    assert_mafia_error(&client.try_resolve(&47), MafiaError::WrongPhase);
+
+    force_dead(&env, &client, 47, &Vec::from_array(&env, [0u32]));
+    client.resolve(&47);
+    assert_eq!(client.get_game(&47).unwrap().phase, PHASE_NIGHT_REVEAL);
+}
+
+// ============================================================================
+// Morning Announcement Tests
+// ============================================================================
+
+#[test]
+fn test_night_death_hidden_until_announced() {
+    let (env, client, players) = begun_game(48, 8, RULE_MORNING_ANNOUNCE);
+    let creator = players.get(0).unwrap();
+    let victim = first_town(&client, 48);
+    run_night(&env, &client, 48, &players, &mafia_kill(&client, 48, victim));
+
+    let summary = client.get_game_summary(&48);
+    assert!(summary.pending_announcement);
+    assert_eq!(summary.last_killed, None);
+    assert_eq!(summary.alive.len(), 8);
+    let result = client.try_submit_action(&48, &creator, &PASS_TARGET);
+    assert_mafia_error(&result, MafiaError::NotYetReady);
+
+    client.announce(&48, &creator);
+    let summary = client.get_game_summary(&48);
+    assert!(!summary.pending_announcement);
+    assert_eq!(summary.last_killed, Some(victim));
+    assert_eq!(summary.alive.len(), 7);
+    assert!(!summary.alive.contains(victim));
+    // The victim may have been the host; ask whoever holds host powers now.
+    let host = client.get_game(&48).unwrap().creator;
+    assert_mafia_error(&client.try_announce(&48, &host), MafiaError::WrongPhase);
+}
+
+#[test]
+fn test_get_game_holds_back_unannounced_night() {
+    let (env, client, players) = begun_game(241, 8, RULE_MORNING_ANNOUNCE | RULE_OPEN_NIGHT);
+    let creator = players.get(0).unwrap();
+    let victim = first_town(&client, 241);
+    run_night(&env, &client, 241, &players, &mafia_kill(&client, 241, victim));
+
+    let game = client.get_game(&241).unwrap();
+    assert!(game.pending_announcement);
+    assert_eq!((game.pending_killed, game.pending_saved, game.last_kill_source), (None, false, None));
+    assert!(game.slots.get(victim).unwrap().alive);
+    assert!(game.graveyard.is_empty() && game.night_reports.is_empty());
+    assert!(client.get_night_reports(&241).is_empty());
+
+    client.announce(&241, &creator);
+    let game = client.get_game(&241).unwrap();
+    assert_eq!(game.last_killed, Some(victim));
+    assert!(!game.slots.get(victim).unwrap().alive);
+    assert_eq!((game.graveyard.len(), game.night_reports.len()), (1, 1));
+}
+
+#[test]
+fn test_unannounced_death_withheld_from_graveyard_notes_and_hook() {
+    let (env, client, _hub) = setup_test();
+    let hook = env.register(MockDeathHook, ());
+    client.set_death_hook(&Some(hook.clone()));
+    let players = seat_humans(&env, &client, 230, 8);
+    let creator = players.get(0).unwrap();
+    configure(&client, 230, &creator, RULE_MORNING_ANNOUNCE);
+    client.begin_game(&230, &creator);
+    let victim = first_town(&client, 230);
+    let victim_role = client.get_game(&230).unwrap().slots.get(victim).unwrap().role;
+    let note = Bytes::from_slice(&env, b"it was slot 3");
+    client.set_sealed_note(&230, &players.get(victim).unwrap(), &note);
+    run_night(&env, &client, 230, &players, &mafia_kill(&client, 230, victim));
+
+    assert!(client.get_graveyard(&230).is_empty());
+    assert_mafia_error(&client.try_get_sealed_note(&230, &victim), MafiaError::StillAlive);
+    assert!(MockDeathHookClient::new(&env, &hook).deaths().is_empty());
+
+    client.announce(&230, &creator);
+    let graves = client.get_graveyard(&230);
+    assert_eq!((graves.len(), graves.get(0).unwrap().slot), (1, victim));
+    assert_eq!(client.get_sealed_note(&230, &victim), Some(note));
+    assert_eq!(MockDeathHookClient::new(&env, &hook).deaths(), Vec::from_array(&env, [(230, victim, victim_role, DEATH_NIGHT_KILL)]));
+}
+
+#[test]
+fn test_without_announce_rule_summary_is_immediate() {
+    let (env, client, players) = begun_game(49, 8, 0);
+    let victim = first_town(&client, 49);
+    run_night(&env, &client, 49, &players, &mafia_kill(&client, 49, victim));
+    let summary = client.get_game_summary(&49);
+    assert!(!summary.pending_announcement);
+    assert_eq!(summary.last_killed, Some(victim));
+}
+
+// ============================================================================
+// Seat Uniqueness Tests
+// ============================================================================
+
+#[test]
+fn test_duplicate_join_rejected() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 50, 2);
+    let result = client.try_join_game(&50, &players.get(1).unwrap());
+    assert_mafia_error(&result, MafiaError::AlreadyJoined);
+    assert_eq!(client.get_game(&50).unwrap().human_count, 2);
+}
+
+#[test]
+#[should_panic(expected = "Address seated twice")]
+fn test_unique_address_invariant_panics_on_duplicate() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 51, 1);
+    let mut game = client.get_game(&51).unwrap();
+    let mut s = game.slots.get(3).unwrap();
+    s.addr = Some(players.get(0).unwrap());
+    game.slots.set(3, s);
+    MafiaDuelContract::assert_unique_addresses(&game);
+}
+
+#[test]
+fn test_hub_points_default_to_wager() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = seat_humans(&env, &client, 52, 2);
+    client.begin_game(&52, &players.get(0).unwrap());
+    assert_eq!(hub_client.started_points(&52), Some((100, 100)));
+}
+
+#[test]
+fn test_role_points_and_player1_outcome() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = seat_humans(&env, &client, 53, 8);
+    configure(&client, 53, &players.get(0).unwrap(), RULE_ROLE_POINTS);
+    client.begin_game(&53, &players.get(0).unwrap());
+
+    let game = client.get_game(&53).unwrap();
+    let pct = |slot: u32| match game.slots.get(slot).unwrap().role {
+        ROLE_MAFIA => 150,
+        ROLE_VILLAGER => 100,
+        _ => 120,
+    };
+    assert_eq!(hub_client.started_points(&53), Some((pct(0), pct(1))));
+
+    play_town_win(&env, &client, 53, &players);
+    let creator_is_town = game.slots.get(0).unwrap().role != ROLE_MAFIA;
+    assert_eq!(hub_client.player1_won(&53), Some(creator_is_town));
+}
+
+// ============================================================================
+// Per-Game Pause Tests
+// ============================================================================
+
+#[test]
+fn test_pause_blocks_actions_until_resumed() {
+    let session_id = 54u32;
+    let (env, client, players) = begun_game_with(session_id, 2, GameConfig { grace_ledgers: 10, ..default_config() });
+    let creator = players.get(0).unwrap();
+
+    assert_mafia_error(&client.try_pause_game(&session_id, &players.get(1).unwrap()), MafiaError::NotCreator);
+    client.pause_game(&session_id, &creator);
+    let event = GamePaused { session_id, paused_at: 100 };
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [event.to_xdr(&env, &client.address)]
+    );
+    let c = commitment(&env, PASS_TARGET, 0);
+    assert_mafia_error(&client.try_submit_commitment(&session_id, &creator, &c), MafiaError::GamePaused);
+    assert_mafia_error(&client.try_pause_game(&session_id, &creator), MafiaError::GamePaused);
+    advance_ledgers(&env, 50);
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::GamePaused);
+
+    client.resume_game(&session_id, &creator);
+    let event = GameResumed { session_id, paused_for: 50 };
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [event.to_xdr(&env, &client.address)]
+    );
+    // The grace clock was pushed back by the pause, so resolve still waits.
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::NotYetReady);
+    client.submit_commitment(&session_id, &creator, &c);
+    assert_mafia_error(&client.try_resume_game(&session_id, &creator), MafiaError::WrongPhase);
+}
+
+#[test]
+fn test_pause_lapses_after_limit() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 55, 1);
+    let creator = players.get(0).unwrap();
+    client.begin_game(&55, &creator);
+    client.pause_game(&55, &creator);
+    advance_ledgers(&env, MAX_PAUSE_LEDGERS);
+    client.submit_commitment(&55, &creator, &commitment(&env, PASS_TARGET, 0));
+    let game = client.get_game(&55).unwrap();
+    assert!(!game.paused);
+    assert_eq!(game.pause_used, MAX_PAUSE_LEDGERS);
+    assert_mafia_error(&client.try_pause_game(&55, &creator), MafiaError::LimitExceeded);
+}
+
+// ============================================================================
+// Win Progress Tests
+// ============================================================================
+
+#[test]
+fn test_win_progress_tracks_board() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 56, 8);
+    assert_mafia_error(&client.try_win_progress(&56), MafiaError::WrongPhase);
+    client.begin_game(&56, &players.get(0).unwrap());
+    // 2 mafia vs 6 town: mafia need 4 more deaths, town need both mafia.
+    assert_eq!(client.win_progress(&56), (4, 2));
+
+    let victim = first_town(&client, 56);
+    run_night(&env, &client, 56, &players, &mafia_kill(&client, 56, victim));
+    assert_eq!(client.win_progress(&56), (3, 2));
+
+    let mafia = slots_with_role(&client, 56, ROLE_MAFIA).get(0).unwrap();
+    run_day(&client, 56, &players, &[mafia; 8]);
+    assert_eq!(client.win_progress(&56), (4, 1));
+}
+
+#[test]
+fn test_win_progress_with_serial_killer() {
+    let (_env, client, _players) = serial_killer_endgame(57, RULE_NEUTRAL_BLOCKS_PARITY, 1);
+    // 2 mafia vs 1 town + 1 serial killer: parity reached, but the serial killer must still die.
+    assert_eq!(client.win_progress(&57), (1, 3));
+}
+
+// ============================================================================
+// Reveal Transcript Tests
+// ============================================================================
+
+#[test]
+fn test_reveal_transcript_recomputes_every_commitment() {
+    let (env, client, players) = begun_game(58, 8, RULE_TRANSCRIPT);
+    assert_mafia_error(&client.try_get_reveal_transcript(&58), MafiaError::WrongPhase);
+
+    play_town_win(&env, &client, 58, &players);
+    let transcript = client.get_reveal_transcript(&58);
+    // Night 1 with eight alive, night 2 after one mafia was lynched.
+    assert_eq!(transcript.len(), 15);
+    for proof in transcript.iter() {
+        let mut node = if proof.path.is_empty() {
+            commitment(&env, proof.target, proof.nonce)
+        } else {
+            component_leaf(&env, proof.index, proof.target, proof.nonce)
+        };
+        for (d, sibling) in proof.path.iter().enumerate() {
+            node = if (proof.index >> d) & 1 == 1 { hash_pair(&env, &sibling, &node) } else { hash_pair(&env, &node, &sibling) };
+        }
+        assert_eq!(node, proof.commitment);
+    }
+}
+
+#[test]
+fn test_transcript_off_by_default() {
+    let (env, client, players) = begun_game(59, 8, 0);
+    play_town_win(&env, &client, 59, &players);
+    assert!(client.get_reveal_transcript(&59).is_empty());
+}
+
+// ============================================================================
+// Address Swap Tests
+// ============================================================================
+
+#[test]
+fn test_swap_address_transfers_lobby_seat() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 60, 3);
+    let (old, new) = (players.get(1).unwrap(), Address::generate(&env));
+    client.swap_address(&60, &old, &new);
+
+    let game = client.get_game(&60).unwrap();
+    assert_eq!(game.slots.get(1).unwrap().addr, Some(new.clone()));
+    assert_eq!(game.human_count, 3);
+    assert_mafia_error(&client.try_swap_address(&60, &old, &Address::generate(&env)), MafiaError::NotInGame);
+    assert_mafia_error(&client.try_swap_address(&60, &new, &players.get(2).unwrap()), MafiaError::AlreadyJoined);
+
+    // Swapping the creator hands over creator rights.
+    let creator2 = Address::generate(&env);
+    client.swap_address(&60, &players.get(0).unwrap(), &creator2);
+    assert_mafia_error(&client.try_begin_game(&60, &players.get(0).unwrap()), MafiaError::NotCreator);
+    client.begin_game(&60, &creator2);
+    let result = client.try_swap_address(&60, &new, &Address::generate(&env));
+    assert_mafia_error(&result, MafiaError::WrongPhase);
+}
+
+// ============================================================================
+// Balance Stats Tests
+// ============================================================================
+
+#[test]
+fn test_balance_stats_count_rounds() {
+    let (env, client, players) = begun_game(61, 8, 0);
+    let empty = BalanceStats { kills_attempted: 0, saves_succeeded: 0, investigations: 0, lynches: 0 };
+    assert_eq!(client.get_balance_stats(), empty);
+
+    // Night 1: the doctor saves the mafia's victim and the sheriff investigates.
+    let victim = slots_with_role(&client, 61, ROLE_VILLAGER).get(0).unwrap();
+    let doctor = slots_with_role(&client, 61, ROLE_DOCTOR).get(0).unwrap();
+    let sheriff = slots_with_role(&client, 61, ROLE_SHERIFF).get(0).unwrap();
+    let mut targets = mafia_kill(&client, 61, victim);
+    targets[doctor as usize] = victim;
+    targets[sheriff as usize] = victim;
+    run_night(&env, &client, 61, &players, &targets);
+    run_day(&client, 61, &players, &[victim; 8]);
+
+    // Night 2: an unsaved kill, no investigation; nobody is lynched.
+    let victim2 = first_town(&client, 61);
+    run_night(&env, &client, 61, &players, &mafia_kill(&client, 61, victim2));
+    run_day(&client, 61, &players, &[PASS_TARGET; 8]);
+
+    let stats = client.get_balance_stats();
+    assert_eq!(stats, BalanceStats { kills_attempted: 2, saves_succeeded: 1, investigations: 1, lynches: 1 });
+}
+
+// ============================================================================
+// Wager Validation Tests
+// ============================================================================
+
+#[test]
+fn test_negative_wager_rejected() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    assert_mafia_error(&client.try_create_game(&62, &creator, &-1), MafiaError::InvalidWager);
+    let second = Address::generate(&env);
+    assert_mafia_error(&client.try_create_and_join(&62, &creator, &second, &-5, &default_config()), MafiaError::InvalidWager);
+    client.create_game(&62, &creator, &0);
+}
+
+#[test]
+fn test_admin_wager_limits_enforced() {
+    let (env, client, _hub) = setup_test();
+    assert_mafia_error(&client.try_set_wager_limits(&-1, &10), MafiaError::InvalidWager);
+    assert_mafia_error(&client.try_set_wager_limits(&50, &10), MafiaError::InvalidWager);
+    client.set_wager_limits(&10, &1_000);
+    assert_eq!(client.get_wager_limits(), (10, 1_000));
+
+    let creator = Address::generate(&env);
+    assert_mafia_error(&client.try_create_game(&63, &creator, &9), MafiaError::InvalidWager);
+    assert_mafia_error(&client.try_create_game(&63, &creator, &1_001), MafiaError::InvalidWager);
+    client.create_game(&63, &creator, &1_000);
+}
+
+// ============================================================================
+// Resolve Readiness Tests
+// ============================================================================
+
+#[test]
+fn test_can_resolve_mirrors_resolve_guards() {
+    let (env, client, _hub) = setup_test();
+    let session_id = 64u32;
+    assert!(!client.can_resolve(&session_id));
+    let players = seat_humans(&env, &client, session_id, 2);
+    let creator = players.get(0).unwrap();
+    client.configure_game(&session_id, &creator, &GameConfig { grace_ledgers: 10, ..default_config() });
+    assert!(!client.can_resolve(&session_id));
+    client.begin_game(&session_id, &creator);
+
+    // Within grace, then paused, then ready.
+    assert!(!client.can_resolve(&session_id));
+    advance_ledgers(&env, 10);
+    client.pause_game(&session_id, &creator);
+    assert!(!client.can_resolve(&session_id));
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::GamePaused);
+    client.resume_game(&session_id, &creator);
+    assert!(client.can_resolve(&session_id));
+    client.resolve(&session_id);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+}
+
+#[test]
+fn test_can_resolve_without_grace() {
+    let (env, client, players) = begun_game(65, 8, 0);
+    assert!(!client.can_resolve(&65));
+    commit_all(&env, &client, 65, &players, &[PASS_TARGET; 8]);
+    assert!(client.can_resolve(&65));
+    reveal_all(&client, 65, &players, &[PASS_TARGET; 8]);
+    client.resolve(&65);
+    let mafia = slots_with_role(&client, 65, ROLE_MAFIA);
+    run_day(&client, 65, &players, &[mafia.get(0).unwrap(); 8]);
+    run_night(&env, &client, 65, &players, &[PASS_TARGET; 8]);
+    run_day(&client, 65, &players, &[mafia.get(1).unwrap(); 8]);
+    assert_eq!(client.get_game(&65).unwrap().phase, PHASE_OVER);
+    assert!(!client.can_resolve(&65));
+}
+
+// ============================================================================
+// Seat Shuffle Tests
+// ============================================================================
+
+#[test]
+fn test_shuffle_seats_varies_by_seed_and_keeps_players() {
+    let (env, client, _hub) = setup_test();
+    let mut orders = Vec::new(&env);
+    for session_id in 66..70u32 {
+        let players = seat_humans(&env, &client, session_id, 4);
+        configure(&client, session_id, &players.get(0).unwrap(), RULE_SHUFFLE_SEATS);
+        client.begin_game(&session_id, &players.get(0).unwrap());
+
+        let game = client.get_game(&session_id).unwrap();
+        for p in players.iter() {
+            assert!(game.slots.iter().any(|s| s.addr == Some(p.clone())));
+        }
+        assert_eq!(game.slots.iter().filter(|s| s.addr.is_some()).count(), 4);
+        let creator_slot = (0..MAX_PLAYERS).find(|&i| game.slots.get(i).unwrap().addr == Some(players.get(0).unwrap()));
+        orders.push_back(creator_slot.unwrap());
+    }
+    // Different seeds move the creator to different seats.
+    assert!(orders.iter().any(|slot| slot != orders.get(0).unwrap()));
+}
+
+// ============================================================================
+// Observer Tests
+// ============================================================================
+
+#[test]
+fn test_observer_reads_but_cannot_act() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 70, 8);
+    let observer = Address::generate(&env);
+    client.add_observer(&70, &observer);
+    assert_eq!(client.get_observers(&70), Vec::from_array(&env, [observer.clone()]));
+    assert_mafia_error(&client.try_add_observer(&70, &observer), MafiaError::AlreadyJoined);
+    assert_mafia_error(&client.try_add_observer(&70, &players.get(1).unwrap()), MafiaError::AlreadyJoined);
+
+    client.begin_game(&70, &players.get(0).unwrap());
+    assert_eq!(client.get_game_summary(&70).alive.len(), 8);
+    let c = commitment(&env, PASS_TARGET, 0);
+    assert_mafia_error(&client.try_submit_commitment(&70, &observer, &c), MafiaError::NotInGame);
+    run_night(&env, &client, 70, &players, &[PASS_TARGET; 8]);
+    assert_mafia_error(&client.try_submit_action(&70, &observer, &PASS_TARGET), MafiaError::NotInGame);
+}
+
+#[test]
+fn test_observer_cannot_join_and_count_is_capped() {
+    let (env, client, _hub) = setup_test();
+    seat_humans(&env, &client, 71, 1);
+    let observer = Address::generate(&env);
+    client.add_observer(&71, &observer);
+    assert_mafia_error(&client.try_join_game(&71, &observer), MafiaError::AlreadyJoined);
+    for _ in 1..MAX_OBSERVERS { client.add_observer(&71, &Address::generate(&env)); }
+    let result = client.try_add_observer(&71, &Address::generate(&env));
+    assert_mafia_error(&result, MafiaError::LimitExceeded);
+}
+
+// ============================================================================
+// Reveal Recheck Tests
+// ============================================================================
+
+#[test]
+fn test_recheck_reveal_matches_stored_commitment() {
+    let (env, client, players) = begun_game(72, 8, 0);
+    assert_mafia_error(&client.try_recheck_reveal(&72, &3, &PASS_TARGET, &3), MafiaError::NoCommitment);
+
+    let mut targets = [PASS_TARGET; 8];
+    targets[3] = 5;
+    commit_all(&env, &client, 72, &players, &targets);
+    assert!(client.recheck_reveal(&72, &3, &5, &3));
+    assert!(!client.recheck_reveal(&72, &3, &5, &4));
+    assert!(!client.recheck_reveal(&72, &3, &6, &3));
+    assert_mafia_error(&client.try_recheck_reveal(&72, &MAX_PLAYERS, &5, &3), MafiaError::InvalidTarget);
+    // Nothing was consumed by the checks.
+    assert!(!client.get_game(&72).unwrap().slots.get(3).unwrap().submitted);
+}
+
+// ============================================================================
+// Bot Masking Tests
+// ============================================================================
+
+#[test]
+fn test_masked_bots_show_as_submitted() {
+    let (env, client, players) = begun_game(73, 3, RULE_MASK_BOTS);
+
+    let game = client.get_game(&73).unwrap();
+    for i in 3..MAX_PLAYERS {
+        let s = game.slots.get(i).unwrap();
+        assert!(s.submitted && s.commitment.is_some());
+    }
+    commit_all(&env, &client, 73, &players, &[PASS_TARGET; 8]);
+    let game = client.get_game(&73).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_REVEAL);
+    for i in 3..MAX_PLAYERS { assert!(game.slots.get(i).unwrap().submitted); }
+
+    // The flags are decoys: a bot mafia still picks a kill when the night resolves.
+    let bot_mafia = slots_with_role(&client, 73, ROLE_MAFIA).iter().any(|m| m >= 3);
+    reveal_all(&client, 73, &players, &[PASS_TARGET; 8]);
+    client.resolve(&73);
+    let game = client.get_game(&73).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.last_killed.is_some(), bot_mafia);
+}
+
+#[test]
+fn test_unmasked_bots_show_nothing() {
+    let (_env, client, _) = begun_game(74, 3, 0);
+    let game = client.get_game(&74).unwrap();
+    for i in 3..MAX_PLAYERS { assert!(!game.slots.get(i).unwrap().submitted); }
+}
+
+// ============================================================================
+// Player Count Tests
+// ============================================================================
+
+fn begin_with_count(session_id: u32, count: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    let (env, client, players) = begun_game_with(session_id, 2, GameConfig { player_count: count, ..default_config() });
+    (env, client, players)
+}
+
+#[test]
+fn test_smaller_tables_assign_only_active_slots() {
+    for (session_id, count, mafia) in [(75u32, 4u32, 1u32), (76, 6, 2), (77, 8, 2)] {
+        let (_env, client, _players) = begin_with_count(session_id, count);
+        let game = client.get_game(&session_id).unwrap();
+        for i in 0..MAX_PLAYERS {
+            let s = game.slots.get(i).unwrap();
+            assert_eq!(s.role == ROLE_NONE, i >= count);
+            assert_eq!(s.alive, i < count);
+        }
+        assert_eq!(slots_with_role(&client, session_id, ROLE_MAFIA).len(), mafia);
+        assert_eq!(slots_with_role(&client, session_id, ROLE_DOCTOR).len(), 1);
+        assert_eq!(slots_with_role(&client, session_id, ROLE_SHERIFF).len(), 1);
+        assert_eq!(client.win_progress(&session_id), (count - 2 * mafia, mafia));
+    }
+}
+
+#[test]
+fn test_unused_slots_excluded_from_play() {
+    let (env, client, players) = begin_with_count(78, 4);
+    let result = client.try_validate_intended_target(&78, &players.get(0).unwrap(), &6);
+    assert_mafia_error(&result, MafiaError::InvalidTarget);
+    // Bots fill the two open active seats; the game plays out without touching slots 4..8.
+    commit_all_remaining(&env, &client, 78, &players);
+    reveal_all(&client, 78, &players, &[PASS_TARGET; 8]);
+    client.resolve(&78);
+    let game = client.get_game(&78).unwrap();
+    for g in game.graveyard.iter() { assert!(g.slot < 4); }
+    assert_mafia_error(&client.try_submit_action(&78, &players.get(0).unwrap(), &5), MafiaError::InvalidTarget);
+}
+
+#[test]
+fn test_player_count_limits_joins() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 79, 5);
+    let creator = players.get(0).unwrap();
+    let small = GameConfig { player_count: 4, ..default_config() };
+    assert_mafia_error(&client.try_configure_game(&79, &creator, &small), MafiaError::InvalidConfig);
+    let tiny = GameConfig { player_count: 3, ..default_config() };
+    assert_mafia_error(&client.try_configure_game(&79, &creator, &tiny), MafiaError::InvalidConfig);
+    client.configure_game(&79, &creator, &GameConfig { player_count: 5, ..default_config() });
+    assert_mafia_error(&client.try_join_game(&79, &Address::generate(&env)), MafiaError::GameFull);
+}
+
+// ============================================================================
+// Sealed Note Tests
+// ============================================================================
+
+#[test]
+fn test_sealed_note_readable_only_after_death() {
+    let (env, client, players) = begun_game(80, 8, 0);
+    let victim = first_town(&client, 80);
+    let note = Bytes::from_slice(&env, b"sheriff: slot 6 is mafia");
+    client.set_sealed_note(&80, &players.get(victim).unwrap(), &note);
+    assert_mafia_error(&client.try_get_sealed_note(&80, &victim), MafiaError::StillAlive);
+
+    let too_long = Bytes::from_slice(&env, &[0u8; (MAX_SEALED_NOTE + 1) as usize]);
+    let result = client.try_set_sealed_note(&80, &players.get(victim).unwrap(), &too_long);
+    assert_mafia_error(&result, MafiaError::TextTooLong);
+
+    run_night(&env, &client, 80, &players, &mafia_kill(&client, 80, victim));
+    assert_eq!(client.get_sealed_note(&80, &victim), Some(note));
+    let result = client.try_set_sealed_note(&80, &players.get(victim).unwrap(), &Bytes::new(&env));
+    assert_mafia_error(&result, MafiaError::NotAlive);
+}
+
+// ============================================================================
+// Auto-Advance Tests
+// ============================================================================
+
+#[test]
+fn test_auto_advance_runs_ai_only_game_to_completion() {
+    let (env, client, _) = begun_game(81, 1, RULE_AUTO_ADVANCE);
+    force_dead(&env, &client, 81, &Vec::from_array(&env, [0u32]));
+
+    client.resolve(&81);
+    let game = client.get_game(&81).unwrap();
+    assert_eq!(game.phase, PHASE_OVER);
+    assert!(game.winner.is_some());
+    assert!(game.day > 1);
+}
+
+#[test]
+fn test_auto_advance_stops_while_humans_live() {
+    let (env, client, players) = begun_game(82, 1, RULE_AUTO_ADVANCE);
+    let creator = players.get(0).unwrap();
+    client.submit_commitment(&82, &creator, &commitment(&env, PASS_TARGET, 0));
+    client.reveal_action(&82, &creator, &PASS_TARGET, &0);
+    client.resolve(&82);
+    let game = client.get_game(&82).unwrap();
+    if game.slots.get(0).unwrap().alive {
+        assert_eq!(game.phase, PHASE_DAY);
+    } else {
+        assert_eq!(game.phase, PHASE_OVER);
+    }
+}
+
+#[test]
+fn test_auto_advance_skips_discussion_without_humans() {
+    let (env, client, _) = begun_game_with(227, 1, GameConfig { rules: RULE_AUTO_ADVANCE, discussion_ledgers: 30, ..default_config() });
+    force_dead(&env, &client, 227, &Vec::from_array(&env, [0u32]));
+
+    client.resolve(&227);
+    let game = client.get_game(&227).unwrap();
+    assert_eq!(game.phase, PHASE_OVER);
+    assert!(game.winner.is_some());
+}
+
+// ============================================================================
+// Blind Day Tests
+// ============================================================================
+
+fn day_with_votes(session_id: u32, rules: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    let (env, client, players) = begun_game(session_id, 8, rules);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    for i in 0..3u32 { client.submit_action(&session_id, &players.get(i).unwrap(), &5); }
+    (env, client, players)
+}
+
+#[test]
+fn test_blind_day_hides_live_votes_until_tally() {
+    let (_env, client, players) = day_with_votes(83, RULE_BLIND_DAY);
+    assert!(client.get_day_votes_live(&83).is_empty());
+    for i in 3..8u32 { client.submit_action(&83, &players.get(i).unwrap(), &PASS_TARGET); }
+    client.resolve(&83);
+    let tally = client.get_last_tally(&83);
+    assert_eq!(tally.get(5), Some(3));
+    assert_eq!(tally.iter().sum::<u32>(), 3);
+    assert_eq!(client.get_game(&83).unwrap().last_voted_out.get(0), Some(5));
+}
+
+#[test]
+fn test_blind_day_masks_votes_in_get_game() {
+    let (_env, client, _players) = day_with_votes(229, RULE_BLIND_DAY);
+    let game = client.get_game(&229).unwrap();
+    for i in 0..3u32 {
+        let s = game.slots.get(i).unwrap();
+        assert!(s.submitted);
+        assert_eq!(s.action, None);
+    }
+}
+
+#[test]
+fn test_open_day_shows_live_votes() {
+    let (env, client, _players) = day_with_votes(84, 0);
+    assert_eq!(client.get_day_votes_live(&84), Vec::from_array(&env, [(0u32, 5u32), (1, 5), (2, 5)]));
+}
+
+// ============================================================================
+// Commitment Preimage Tests
+// ============================================================================
+
+#[test]
+fn test_commitment_preimage_hashes_to_commitment() {
+    let (env, client, _hub) = setup_test();
+    for (target, nonce) in [(0u32, 0u64), (5, 42), (PASS_TARGET, u64::MAX)] {
+        let preimage = client.commitment_preimage(&target, &nonce);
+        assert_eq!(preimage.len(), 12);
+        let hashed: BytesN<32> = env.crypto().sha256(&preimage).into();
+        assert_eq!(hashed, MafiaDuelContract::compute_commitment(&env, target, nonce));
+        assert_eq!(hashed, commitment(&env, target, nonce));
+    }
+}
+
+// ============================================================================
+// Peaceful Night Tests
+// ============================================================================
+
+#[test]
+fn test_all_human_all_pass_night_is_peaceful() {
+    let (env, client, players) = begun_game(85, 8, 0);
+    run_night(&env, &client, 85, &players, &[PASS_TARGET; 8]);
+
+    let game = client.get_game(&85).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.last_killed, None);
+    assert!(!game.last_saved);
+    assert!(game.graveyard.is_empty());
+    assert_eq!(game.night_log, Vec::from_array(&env, [NightRecord { day: 1, deaths: 0 }]));
+
+    // A bloody second night is logged with its death count.
+    run_day(&client, 85, &players, &[PASS_TARGET; 8]);
+    let victim = first_town(&client, 85);
+    run_night(&env, &client, 85, &players, &mafia_kill(&client, 85, victim));
+    assert_eq!(client.get_game(&85).unwrap().night_log.get(1), Some(NightRecord { day: 2, deaths: 1 }));
+}
+
+#[test]
+fn test_night_log_waits_for_announcement() {
+    let (env, client, players) = begun_game(86, 8, RULE_MORNING_ANNOUNCE);
+    run_night(&env, &client, 86, &players, &[PASS_TARGET; 8]);
+    assert!(client.get_game(&86).unwrap().night_log.is_empty());
+    client.announce(&86, &players.get(0).unwrap());
+    assert_eq!(client.get_game(&86).unwrap().night_log.len(), 1);
+}
+
+// ============================================================================
+// Death Reveal Tests
+// ============================================================================
+
+#[test]
+fn test_lynch_reveals_role_but_night_kill_does_not() {
+    let (env, client, players) = begun_game(87, 8, RULE_REVEAL_ON_LYNCH);
+    let victim = first_town(&client, 87);
+    run_night(&env, &client, 87, &players, &mafia_kill(&client, 87, victim));
+    let mafia = slots_with_role(&client, 87, ROLE_MAFIA).get(0).unwrap();
+    run_day(&client, 87, &players, &[mafia; 8]);
+
+    let graves = client.get_graveyard(&87);
+    assert_eq!(graves.len(), 2);
+    assert_eq!(graves.get(0).unwrap().revealed_role, None);
+    assert_eq!(graves.get(1).unwrap().revealed_role, Some(ROLE_MAFIA));
+}
+
+#[test]
+fn test_night_kill_reveal_flag() {
+    let (env, client, players) = begun_game(88, 8, RULE_REVEAL_ON_NIGHTKILL);
+    let victim = first_town(&client, 88);
+    let role = client.get_game(&88).unwrap().slots.get(victim).unwrap().role;
+    run_night(&env, &client, 88, &players, &mafia_kill(&client, 88, victim));
+    let mafia = slots_with_role(&client, 88, ROLE_MAFIA).get(0).unwrap();
+    run_day(&client, 88, &players, &[mafia; 8]);
+
+    let graves = client.get_graveyard(&88);
+    assert_eq!(graves.get(0).unwrap().revealed_role, Some(role));
+    assert_eq!(graves.get(1).unwrap().revealed_role, None);
+}
+
+// ============================================================================
+// AI Prediction Tests
+// ============================================================================
+
+#[test]
+fn test_predicted_ai_actions_match_resolution() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 89, 1);
+    // The lowest-slot mafia member's pick is the kill, so one prediction names it.
+    client.configure_game(&89, &players.get(0).unwrap(), &GameConfig { mafia_tie_breaker: MAFIA_TIE_FIRST, ..default_config() });
+    client.begin_game(&89, &players.get(0).unwrap());
+    assert_mafia_error(&client.try_predict_ai_action(&89, &1), MafiaError::WrongPhase);
+    commit_all(&env, &client, 89, &players, &[PASS_TARGET; 8]);
+    reveal_all(&client, 89, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.predict_ai_action(&89, &0), None);
+
+    let mut predicted_kill = None;
+    for m in slots_with_role(&client, 89, ROLE_MAFIA).iter() {
+        if let Some(t) = client.predict_ai_action(&89, &m) { predicted_kill = Some(t); break; }
+    }
+    client.resolve(&89);
+    assert_eq!(client.get_game(&89).unwrap().last_killed, predicted_kill);
+
+    let game = client.get_game(&89).unwrap();
+    let mut expected = [0u32; 8];
+    for i in 1..MAX_PLAYERS {
+        if !game.slots.get(i).unwrap().alive { continue; }
+        expected[client.predict_ai_action(&89, &i).unwrap() as usize] += 1;
+    }
+    run_day(&client, 89, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_last_tally(&89), Vec::from_array(&env, expected));
+}
+
+#[test]
+fn test_ai_intentions_match_resolution_in_practice() {
+    let (env, client, players) = begun_game_with(205, 1, GameConfig { practice: true, mafia_tie_breaker: MAFIA_TIE_FIRST, ..default_config() });
+    commit_all(&env, &client, 205, &players, &[PASS_TARGET; 8]);
+    reveal_all(&client, 205, &players, &[PASS_TARGET; 8]);
+    let night = client.ai_intentions(&205);
+    assert_eq!(night.get(0).unwrap(), None);
+    for i in 1..MAX_PLAYERS {
+        assert_eq!(night.get(i).unwrap(), client.predict_ai_action(&205, &i));
+    }
+    let kill = slots_with_role(&client, 205, ROLE_MAFIA).iter().find_map(|m| night.get(m).unwrap());
+    client.resolve(&205);
+    assert_eq!(client.get_game(&205).unwrap().last_killed, kill);
+
+    let mut expected = [0u32; 8];
+    for t in client.ai_intentions(&205).iter().flatten() { expected[t as usize] += 1; }
+    run_day(&client, 205, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_last_tally(&205), Vec::from_array(&env, expected));
+}
+
+#[test]
+fn test_ai_intentions_need_practice_mode() {
+    let (env, client, players) = begun_game(206, 1, 0);
+    run_night(&env, &client, 206, &players, &[PASS_TARGET; 8]);
+    assert_mafia_error(&client.try_ai_intentions(&206), MafiaError::RuleDisabled);
+}
+
+// ============================================================================
+// AI Suspicion Tests
+// ============================================================================
+
+/// One human game in PHASE_DAY whose public sheriff result flags a living mafia.
+fn suspicion_day(session_id: u32, rules: u32) -> (Env, MafiaDuelContractClient<'static>, u32) {
+    let (env, client, players) = begun_game(session_id, 1, rules);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    let mut game = client.get_game(&session_id).unwrap();
+    let suspect = slots_with_role(&client, session_id, ROLE_MAFIA).iter().find(|&m| game.slots.get(m).unwrap().alive).unwrap();
+    game.last_investigated = Some(suspect);
+    game.invest_is_mafia   = true;
+    env.as_contract(&client.address, || {
+        env.storage().temporary().set(&DataKey::Game(session_id), &game);
+    });
+    (env, client, suspect)
+}
+
+fn predicted_votes(client: &MafiaDuelContractClient, session_id: u32, role_is_mafia: bool) -> Vec<u32> {
+    let game = client.get_game(&session_id).unwrap();
+    let mut out = Vec::new(&client.env);
+    for i in 1..MAX_PLAYERS {
+        let s = game.slots.get(i).unwrap();
+        if !s.alive || (s.role == ROLE_MAFIA) != role_is_mafia { continue; }
+        out.push_back(client.predict_ai_action(&session_id, &i).unwrap());
+    }
+    out
+}
+
+#[test]
+fn test_ai_town_leans_toward_flagged_mafia() {
+    let (_env, client, suspect) = suspicion_day(90, RULE_AI_SUSPICION);
+    let town = predicted_votes(&client, 90, false);
+    let on_suspect = town.iter().filter(|&t| t == suspect).count();
+    assert!(on_suspect * 2 > town.len() as usize);
+
+    let (_env, client, suspect) = suspicion_day(90, 0);
+    let plain = predicted_votes(&client, 90, false).iter().filter(|&t| t == suspect).count();
+    assert!(plain < on_suspect);
+}
+
+#[test]
+fn test_ai_mafia_never_vote_a_teammate_under_suspicion() {
+    let (_env, client, _suspect) = suspicion_day(91, RULE_AI_SUSPICION);
+    let game = client.get_game(&91).unwrap();
+    let votes = predicted_votes(&client, 91, true);
+    assert!(!votes.is_empty());
+    for t in votes.iter() {
+        assert_ne!(game.slots.get(t).unwrap().role, ROLE_MAFIA);
+    }
+}
+
+// ============================================================================
+// Session Cleanup Tests
+// ============================================================================
+
+#[test]
+fn test_admin_clears_finished_session_for_reuse() {
+    let (env, client, players) = begun_game(92, 8, RULE_TRANSCRIPT);
+    client.set_sealed_note(&92, &players.get(1).unwrap(), &Bytes::from_slice(&env, b"note"));
+    play_town_win(&env, &client, 92, &players);
+    assert_eq!(client.get_game(&92).unwrap().phase, PHASE_OVER);
+
+    client.admin_clear_session(&92);
+    assert!(client.get_game(&92).is_none());
+    assert_mafia_error(&client.try_get_reveal_transcript(&92), MafiaError::GameNotFound);
+    client.create_game(&92, &Address::generate(&env), &100);
+    assert_eq!(client.get_game(&92).unwrap().phase, PHASE_LOBBY);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().temporary().has(&DataKey::SealedNote(92, 1)));
+    });
+}
+
+#[test]
+fn test_admin_clear_refuses_live_game_until_abandoned() {
+    let (env, client, _) = begun_game_with(93, 8, GameConfig { grace_ledgers: 100, ..default_config() });
+    assert_mafia_error(&client.try_admin_clear_session(&93), MafiaError::NotYetReady);
+    advance_ledgers(&env, 100 + ABANDON_LEDGERS);
+    client.admin_clear_session(&93);
+    assert!(client.get_game(&93).is_none());
+}
+
+// ============================================================================
+// Mafia Pass Rule Tests
+// ============================================================================
+
+#[test]
+fn test_mafia_must_kill_rejects_pass_reveal() {
+    let (env, client, players) = begun_game(94, 8, RULE_MAFIA_MUST_KILL);
+    let mafia = slots_with_role(&client, 94, ROLE_MAFIA).get(0).unwrap();
+    commit_all(&env, &client, 94, &players, &[PASS_TARGET; 8]);
+    let player = players.get(mafia).unwrap();
+    assert_mafia_error(&client.try_reveal_action(&94, &player, &PASS_TARGET, &(mafia as u64)), MafiaError::InvalidTarget);
+
+    // Town may still pass under the rule.
+    let town = first_town(&client, 94);
+    client.reveal_action(&94, &players.get(town).unwrap(), &PASS_TARGET, &(town as u64));
+}
+
+#[test]
+fn test_mafia_must_kill_night_always_kills() {
+    let (env, client, players) = begun_game(240, 8, RULE_MAFIA_MUST_KILL);
+    let mafia = slots_with_role(&client, 240, ROLE_MAFIA);
+    assert!(!client.decision_space(&240, &players.get(mafia.get(0).unwrap()).unwrap()).can_pass);
+    assert!(client.decision_space(&240, &players.get(first_town(&client, 240)).unwrap()).can_pass);
+
+    // Neither mafia reveals, so no mafia target is on record when the night resolves.
+    commit_all(&env, &client, 240, &players, &[PASS_TARGET; 8]);
+    for i in 0..8 {
+        if !mafia.contains(i) { client.reveal_action(&240, &players.get(i).unwrap(), &PASS_TARGET, &(i as u64)); }
+    }
+    client.resolve(&240);
+    let game = client.get_game(&240).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    let victim = game.last_killed.unwrap();
+    assert!(!mafia.contains(victim) && !game.slots.get(victim).unwrap().alive);
+}
+
+#[test]
+fn test_mafia_may_pass_lets_ai_mafia_skip_some_nights() {
+    let mut ai_passed = false;
+    for sid in 95..111 {
+        for (rules, may_pass) in [(RULE_MAFIA_MUST_KILL, false), (0, true)] {
+            let (env, client, _hub) = setup_test();
+            let players = seat_humans(&env, &client, sid, 1);
+            configure(&client, sid, &players.get(0).unwrap(), rules);
+            client.begin_game(&sid, &players.get(0).unwrap());
+            commit_all(&env, &client, sid, &players, &[PASS_TARGET; 8]);
+            for m in slots_with_role(&client, sid, ROLE_MAFIA).iter().filter(|&m| m > 0) {
+                let passed = client.predict_ai_action(&sid, &m).is_none();
+                assert!(may_pass || !passed);
+                ai_passed |= passed;
+            }
+        }
+    }
+    assert!(ai_passed);
+}
+
+// ============================================================================
+// Mafia Tie Tests
+// ============================================================================
+
+/// Night one of an 8-human game where the two mafia name different villagers under `mode`.
+/// Returns the game after resolution and the (lower mafia's, higher mafia's) targets.
+fn split_kill(session_id: u32, mode: u32) -> (Game, u32, u32) {
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { mafia_tie_breaker: mode, ..default_config() });
+    let mafia     = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let villagers = slots_with_role(&client, session_id, ROLE_VILLAGER);
+    let (a, b) = (villagers.get(0).unwrap(), villagers.get(1).unwrap());
+    let mut targets = [PASS_TARGET; 8];
+    targets[mafia.get(0).unwrap() as usize] = a;
+    targets[mafia.get(1).unwrap() as usize] = b;
+    run_night(&env, &client, session_id, &players, &targets);
+    (client.get_game(&session_id).unwrap(), a, b)
+}
+
+#[test]
+fn test_split_mafia_vote_first_member_decides() {
+    let (game, a, b) = split_kill(207, MAFIA_TIE_FIRST);
+    assert_eq!(game.last_killed, Some(a));
+    assert!(!game.slots.get(a).unwrap().alive);
+    assert!(game.slots.get(b).unwrap().alive);
+}
+
+#[test]
+fn test_split_mafia_vote_kills_no_one() {
+    let (game, _a, _b) = split_kill(208, MAFIA_TIE_NO_KILL);
+    assert_eq!(game.last_killed, None);
+    assert!(game.slots.iter().all(|s| s.alive));
+}
+
+#[test]
+fn test_split_mafia_vote_picks_one_target_at_random() {
+    let mut killed = [false; 2];
+    for session_id in 209..217u32 {
+        let (game, a, b) = split_kill(session_id, MAFIA_TIE_RANDOM);
+        assert_eq!(game.slots.iter().filter(|s| !s.alive).count(), 1);
+        let k = game.last_killed.unwrap();
+        assert!(k == a || k == b);
+        killed[(k == b) as usize] = true;
+    }
+    // Both targets come up across sessions, so it isn't the first mafia's choice in disguise.
+    assert_eq!(killed, [true, true]);
+}
+
+#[test]
+fn test_default_config_settles_mafia_ties_randomly() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 217, 4);
+    assert_eq!(client.get_game(&217).unwrap().config.mafia_tie_breaker, MAFIA_TIE_RANDOM);
+    let bad = GameConfig { mafia_tie_breaker: MAFIA_TIE_FIRST + 1, ..default_config() };
+    assert_mafia_error(&client.try_configure_game(&217, &players.get(0).unwrap(), &bad), MafiaError::InvalidConfig);
+}
+
+// ============================================================================
+// Bracket Tests
+// ============================================================================
+
+#[test]
+fn test_create_bracket_opens_sequential_lobbies() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    let config = GameConfig { rules: RULE_VIGILANTE, player_count: 6, ..default_config() };
+    let ids = client.create_bracket(&200, &4, &creator, &100, &config);
+    assert_eq!(ids, Vec::from_array(&env, [200, 201, 202, 203]));
+    for sid in ids.iter() {
+        let game = client.get_game(&sid).unwrap();
+        assert_eq!(game.phase, PHASE_LOBBY);
+        assert_eq!(game.slots.get(0).unwrap().addr, Some(creator.clone()));
+        assert_eq!(game.wager, 100);
+        assert_eq!((game.config.rules, game.config.player_count), (RULE_VIGILANTE, 6));
+    }
+}
+
+#[test]
+fn test_create_bracket_collision_creates_nothing() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    client.create_game(&212, &creator, &100);
+    assert_mafia_error(&client.try_create_bracket(&210, &4, &creator, &100, &default_config()), MafiaError::SessionExists);
+    assert!(client.get_game(&210).is_none());
+    assert_mafia_error(&client.try_create_bracket(&220, &(MAX_BRACKET + 1), &creator, &100, &default_config()), MafiaError::LimitExceeded);
+}
+
+// ============================================================================
+// Session Status Tests
+// ============================================================================
+
+#[test]
+fn test_session_status_tracks_lifecycle() {
+    let (env, client, _hub) = setup_test();
+    assert_eq!(client.session_status(&112), STATUS_NONEXISTENT);
+    let players = seat_humans(&env, &client, 112, 8);
+    assert_eq!(client.session_status(&112), STATUS_LOBBY);
+    client.begin_game(&112, &players.get(0).unwrap());
+    assert_eq!(client.session_status(&112), STATUS_IN_PROGRESS);
+    play_town_win(&env, &client, 112, &players);
+    assert_eq!(client.session_status(&112), STATUS_OVER);
+}
+
+// ============================================================================
+// No-Powers Mode Tests
+// ============================================================================
+
+#[test]
+fn test_no_powers_leaves_only_the_mafia_kill() {
+    let (env, client, players) = begun_game(113, 8, RULE_NO_POWERS);
+    let doctor  = slots_with_role(&client, 113, ROLE_DOCTOR).get(0).unwrap();
+    let sheriff = slots_with_role(&client, 113, ROLE_SHERIFF).get(0).unwrap();
+    let victim  = first_town(&client, 113);
+    let space = client.decision_space(&113, &players.get(doctor).unwrap());
+    assert!(space.legal_targets.is_empty() && space.can_pass);
+    assert_mafia_error(
+        &client.try_validate_intended_target(&113, &players.get(sheriff).unwrap(), &victim),
+        MafiaError::InvalidTarget,
+    );
+
+    // The doctor commits to a save that can never be revealed; the kill lands regardless.
+    let mut targets = mafia_kill(&client, 113, victim);
+    targets[doctor as usize] = victim;
+    commit_all(&env, &client, 113, &players, &targets);
+    for i in 0..MAX_PLAYERS {
+        if i == doctor { continue; }
+        client.reveal_action(&113, &players.get(i).unwrap(), &targets[i as usize], &(i as u64));
+    }
+    let doctor_player = players.get(doctor).unwrap();
+    assert_mafia_error(&client.try_reveal_action(&113, &doctor_player, &victim, &(doctor as u64)), MafiaError::InvalidTarget);
+    client.resolve(&113);
+    let game = client.get_game(&113).unwrap();
+    assert!(!game.slots.get(victim).unwrap().alive);
+    assert!(!game.last_saved);
+    assert_eq!(game.last_investigated, None);
+}
+
+#[test]
+fn test_no_powers_ai_roles_stay_inert() {
+    let (env, client, players) = begun_game(114, 1, RULE_NO_POWERS);
+    commit_all(&env, &client, 114, &players, &[PASS_TARGET; 8]);
+    reveal_all(&client, 114, &players, &[PASS_TARGET; 8]);
+    let game = client.get_game(&114).unwrap();
+    for i in 1..MAX_PLAYERS {
+        let role = game.slots.get(i).unwrap().role;
+        let action = client.predict_ai_action(&114, &i);
+        if role == ROLE_MAFIA { assert!(action.is_some()); } else { assert_eq!(action, None); }
+    }
+}
+
+// ============================================================================
+// Role Verification Tests
+// ============================================================================
+
+#[test]
+fn test_verify_my_role_against_published_seed() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 115, 8);
+    configure(&client, 115, &players.get(0).unwrap(), RULE_SHUFFLE_SEATS | RULE_VIGILANTE);
+    assert_eq!(client.get_role_seed(&115), None);
+    client.begin_game(&115, &players.get(0).unwrap());
+    let seed = client.get_role_seed(&115).unwrap();
+    let game = client.get_game(&115).unwrap();
+    for i in 0..MAX_PLAYERS {
+        let s = game.slots.get(i).unwrap();
+        let player = s.addr.unwrap();
+        assert!(client.verify_my_role(&115, &player, &s.role, &seed));
+        assert!(!client.verify_my_role(&115, &player, &((s.role + 1) % ROLE_COUNT), &seed));
+    }
+    let wrong_seed = BytesN::from_array(&env, &[7u8; 32]);
+    let s = game.slots.get(0).unwrap();
+    assert!(!client.verify_my_role(&115, &s.addr.unwrap(), &s.role, &wrong_seed));
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_new_game_starts_with_default_config() {
+    let (env, client, _hub) = setup_test();
+    seat_humans(&env, &client, 116, 1);
+    assert_eq!(client.get_default_config(), default_config());
+    assert_eq!(client.get_config(&116), default_config());
+    assert_mafia_error(&client.try_get_config(&117), MafiaError::GameNotFound);
+}
+
+#[test]
+fn test_config_round_trips_through_create() {
+    let (env, client, _hub) = setup_test();
+    setup_escrow(&env, &client);
+    let config = GameConfig { rules: RULE_WATCHER | RULE_BLIND_DAY, grace_ledgers: 50, tie_breaker: TIE_RANDOM, day_eliminations: 2, player_count: 7, consolation_bps: 250, ability_charges: 2, recharge_every: 3, discussion_ledgers: 20, practice: true, mafia_tie_breaker: MAFIA_TIE_NO_KILL, stake_timing: STAKE_ON_BEGIN };
+    client.create_game_with_config(&118, &Address::generate(&env), &100, &config);
+    assert_eq!(client.get_config(&118), config);
+    let bad = GameConfig { player_count: MAX_PLAYERS + 1, ..default_config() };
+    assert_mafia_error(&client.try_create_game_with_config(&119, &Address::generate(&env), &100, &bad), MafiaError::InvalidConfig);
+    assert!(client.get_game(&119).is_none());
+}
+
+// ============================================================================
+// Suspect Tie-Break Tests
+// ============================================================================
+
+/// Eight humans under TIE_SUSPECT; the sheriff checks a mafia, then the day ties 4-4 between `a` and `b`.
+fn suspect_tie(session_id: u32, pick: impl Fn(&MafiaDuelContractClient, u32) -> (u32, u32)) -> (MafiaDuelContractClient<'static>, u32, u32, u32) {
+    let (env, client, players) = begun_game_with(session_id, 8, GameConfig { tie_breaker: TIE_SUSPECT, ..default_config() });
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    let sheriff = slots_with_role(&client, session_id, ROLE_SHERIFF).get(0).unwrap();
+    let mut night = [PASS_TARGET; 8];
+    night[sheriff as usize] = mafia;
+    run_night(&env, &client, session_id, &players, &night);
+    assert!(client.get_game(&session_id).unwrap().invest_is_mafia);
+
+    let (a, b) = pick(&client, mafia);
+    let mut votes = [a; 8];
+    votes[a as usize] = b;
+    let mut for_b = 1;
+    for i in 0..8u32 {
+        if i != a && i != b && for_b < 4 { votes[i as usize] = b; for_b += 1; }
+    }
+    run_day(&client, session_id, &players, &votes);
+    (client, mafia, a, b)
+}
+
+#[test]
+fn test_suspect_tie_break_eliminates_flagged_candidate() {
+    let (client, mafia, _a, _b) = suspect_tie(120, |client, mafia| (mafia, first_town(client, 120)));
+    assert_eq!(client.get_last_tally(&120).get(mafia), Some(4));
+    assert_eq!(client.get_game(&120).unwrap().last_voted_out, Vec::from_array(&client.env, [mafia]));
+}
+
+#[test]
+fn test_suspect_tie_break_falls_back_to_random() {
+    let (client, mafia, a, b) = suspect_tie(121, |client, _mafia| {
+        let town = slots_with_role(client, 121, ROLE_VILLAGER);
+        (town.get(0).unwrap(), town.get(1).unwrap())
+    });
+    let out = client.get_game(&121).unwrap().last_voted_out;
+    assert_eq!(out.len(), 1);
+    let lynched = out.get(0).unwrap();
+    assert!(lynched == a || lynched == b);
+    assert_ne!(lynched, mafia);
+}
+
+// ============================================================================
+// Death Hook Tests
+// ============================================================================
+
+#[test]
+fn test_death_hook_hears_each_death() {
+    let (env, client, _hub) = setup_test();
+    let hook = env.register(MockDeathHook, ());
+    client.set_death_hook(&Some(hook.clone()));
+    let players = seat_humans(&env, &client, 122, 8);
+    client.begin_game(&122, &players.get(0).unwrap());
+    let victim = first_town(&client, 122);
+    let victim_role = client.get_game(&122).unwrap().slots.get(victim).unwrap().role;
+    run_night(&env, &client, 122, &players, &mafia_kill(&client, 122, victim));
+    let mafia = slots_with_role(&client, 122, ROLE_MAFIA).get(0).unwrap();
+    run_day(&client, 122, &players, &[mafia; 8]);
+
+    let deaths = MockDeathHookClient::new(&env, &hook).deaths();
+    assert_eq!(deaths, Vec::from_array(&env, [
+        (122, victim, victim_role, DEATH_NIGHT_KILL),
+        (122, mafia, ROLE_MAFIA, DEATH_LYNCH),
+    ]));
+
+    client.set_death_hook(&None);
+    run_night(&env, &client, 122, &players, &mafia_kill(&client, 122, first_town(&client, 122)));
+    assert_eq!(MockDeathHookClient::new(&env, &hook).deaths().len(), 2);
+}
+
+#[test]
+fn test_failing_death_hook_does_not_stall_game() {
+    let (env, client, _hub) = setup_test();
+    client.set_death_hook(&Some(env.register(BrokenDeathHook, ())));
+    let players = seat_humans(&env, &client, 123, 8);
+    client.begin_game(&123, &players.get(0).unwrap());
+    let victim = first_town(&client, 123);
+    run_night(&env, &client, 123, &players, &mafia_kill(&client, 123, victim));
+    let game = client.get_game(&123).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert!(!game.slots.get(victim).unwrap().alive);
+}
+
+// ============================================================================
+// Surviving Role Count Tests
+// ============================================================================
+
+#[test]
+fn test_surviving_role_counts_in_open_mode() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 124, 8);
+    configure(&client, 124, &players.get(0).unwrap(), RULE_OPEN_COUNTS);
+    assert_mafia_error(&client.try_get_surviving_role_counts(&124), MafiaError::WrongPhase);
+    client.begin_game(&124, &players.get(0).unwrap());
+    assert_eq!(client.get_surviving_role_counts(&124), Vec::from_array(&env, [2, 4, 1, 1, 0, 0, 0]));
+    let doctor = slots_with_role(&client, 124, ROLE_DOCTOR).get(0).unwrap();
+    run_night(&env, &client, 124, &players, &mafia_kill(&client, 124, doctor));
+    assert_eq!(client.get_surviving_role_counts(&124), Vec::from_array(&env, [2, 4, 0, 1, 0, 0, 0]));
+}
+
+#[test]
+fn test_surviving_role_counts_blocked_in_hidden_mode() {
+    let (env, client, players) = begun_game(125, 8, 0);
+    assert_mafia_error(&client.try_get_surviving_role_counts(&125), MafiaError::RuleDisabled);
+    play_town_win(&env, &client, 125, &players);
+    assert_eq!(client.get_surviving_role_counts(&125).get(ROLE_MAFIA), Some(0));
+}
+
+// ============================================================================
+// Bot Name Tests
+// ============================================================================
+
+#[test]
+fn test_creator_names_bot_seats() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 126, 2);
+    let creator = players.get(0).unwrap();
+    let name = Bytes::from_slice(&env, b"Silent Sam");
+    client.name_bot(&126, &creator, &5, &name);
+    let seats = client.get_players(&126);
+    assert_eq!(seats.len(), MAX_PLAYERS);
+    assert_eq!(seats.get(5).unwrap(), Seat { addr: None, bot_name: Some(name) });
+    assert_eq!(seats.get(1).unwrap(), Seat { addr: Some(players.get(1).unwrap()), bot_name: None });
+
+    assert_mafia_error(&client.try_name_bot(&126, &creator, &1, &Bytes::from_slice(&env, b"x")), MafiaError::InvalidTarget);
+    let long = Bytes::from_array(&env, &[b'a'; (MAX_BOT_NAME + 1) as usize]);
+    assert_mafia_error(&client.try_name_bot(&126, &creator, &6, &long), MafiaError::TextTooLong);
+    assert_mafia_error(&client.try_name_bot(&126, &players.get(1).unwrap(), &6, &Bytes::new(&env)), MafiaError::NotCreator);
+}
+
+#[test]
+fn test_joining_a_named_seat_clears_the_name() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 127, 1);
+    client.name_bot(&127, &players.get(0).unwrap(), &1, &Bytes::from_slice(&env, b"Detective Bot"));
+    client.join_game(&127, &Address::generate(&env));
+    assert_eq!(client.get_players(&127).get(1).unwrap().bot_name, None);
+}
+
+// ============================================================================
+// Win Check Ordering Tests
+// ============================================================================
+
+/// Eight humans with three town left alive; tonight's kill brings the mafia to parity.
+fn parity_night(session_id: u32, rules: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>, Vec<u32>) {
+    let (env, client, players) = begun_game(session_id, 8, rules);
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    let mut victims = Vec::new(&env);
+    let mut spared = 0;
+    for i in 0..MAX_PLAYERS {
+        if mafia.contains(i) { continue; }
+        if spared < 3 { spared += 1; } else { victims.push_back(i); }
+    }
+    force_dead(&env, &client, session_id, &victims);
+    run_night(&env, &client, session_id, &players, &mafia_kill(&client, session_id, first_town(&client, session_id)));
+    (env, client, players, mafia)
+}
+
+#[test]
+fn test_parity_kill_ends_game_before_day_by_default() {
+    let (_env, client, _players, _mafia) = parity_night(128, 0);
+    let game = client.get_game(&128).unwrap();
+    assert_eq!(game.phase, PHASE_OVER);
+    assert_eq!(game.winner, Some(TEAM_MAFIA));
+}
+
+#[test]
+fn test_day_before_parity_gives_town_a_lynch() {
+    let (_env, client, players, mafia) = parity_night(129, RULE_DAY_BEFORE_PARITY);
+    let game = client.get_game(&129).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.winner, None);
+
+    let target = mafia.get(0).unwrap();
+    let mut votes = [target; 8];
+    for m in mafia.iter() { votes[m as usize] = PASS_TARGET; }
+    run_day(&client, 129, &players, &votes);
+    let game = client.get_game(&129).unwrap();
+    assert_eq!(game.last_voted_out.get(0), Some(target));
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert_eq!(game.winner, None);
+}
+
+#[test]
+fn test_day_before_parity_still_ends_on_a_failed_lynch() {
+    let (_env, client, players, _mafia) = parity_night(130, RULE_DAY_BEFORE_PARITY);
+    run_day(&client, 130, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&130).unwrap().winner, Some(TEAM_MAFIA));
+}
+
+// ============================================================================
+// Constants Tests
+// ============================================================================
+
+#[test]
+fn test_get_constants_mirrors_consts() {
+    let (_env, client, _hub) = setup_test();
+    let c = client.get_constants();
+    assert_eq!((c.pass_target, c.max_players, c.min_players), (PASS_TARGET, MAX_PLAYERS, MIN_PLAYERS));
+    assert_eq!(
+        [c.role_mafia, c.role_villager, c.role_doctor, c.role_sheriff, c.role_vigilante, c.role_serial_killer, c.role_none],
+        [ROLE_MAFIA, ROLE_VILLAGER, ROLE_DOCTOR, ROLE_SHERIFF, ROLE_VIGILANTE, ROLE_SERIAL_KILLER, ROLE_NONE],
+    );
+    assert_eq!(
+        [c.phase_lobby, c.phase_night_commit, c.phase_night_reveal, c.phase_day, c.phase_over],
+        [PHASE_LOBBY, PHASE_NIGHT_COMMIT, PHASE_NIGHT_REVEAL, PHASE_DAY, PHASE_OVER],
+    );
+    assert_eq!([c.team_mafia, c.team_town, c.team_serial_killer], [TEAM_MAFIA, TEAM_TOWN, TEAM_SERIAL_KILLER]);
+}
+
+// ============================================================================
+// Commitment Lock Tests
+// ============================================================================
+
+#[test]
+fn test_update_commitment_until_locked() {
+    let (env, client, players) = begun_game(131, 2, 0);
+    let p0 = players.get(0).unwrap();
+    assert_mafia_error(&client.try_lock_commitment(&131, &p0), MafiaError::NoCommitment);
+    client.submit_commitment(&131, &p0, &commitment(&env, PASS_TARGET, 1));
+    client.update_commitment(&131, &p0, &commitment(&env, PASS_TARGET, 0));
+    client.lock_commitment(&131, &p0);
+    assert_mafia_error(&client.try_update_commitment(&131, &p0, &commitment(&env, PASS_TARGET, 9)), MafiaError::AlreadyActed);
+
+    // The locked (updated) commitment reveals as usual.
+    client.submit_commitment(&131, &players.get(1).unwrap(), &commitment(&env, PASS_TARGET, 1));
+    reveal_all(&client, 131, &players, &[PASS_TARGET; 8]);
+    assert!(client.get_game(&131).unwrap().slots.get(0).unwrap().submitted);
+}
+
+// ============================================================================
+// Open Night Tests
+// ============================================================================
+
+#[test]
+fn test_open_night_publishes_every_target() {
+    let (env, client, players) = begun_game(132, 8, RULE_OPEN_NIGHT);
+    let victim  = first_town(&client, 132);
+    let doctor  = slots_with_role(&client, 132, ROLE_DOCTOR).get(0).unwrap();
+    let sheriff = slots_with_role(&client, 132, ROLE_SHERIFF).get(0).unwrap();
+    let mafia   = slots_with_role(&client, 132, ROLE_MAFIA).get(1).unwrap();
+    let mut targets = mafia_kill(&client, 132, victim);
+    targets[doctor as usize]  = victim;
+    targets[sheriff as usize] = mafia;
+    run_night(&env, &client, 132, &players, &targets);
+
+    let reports = client.get_night_reports(&132);
+    assert_eq!(reports, Vec::from_array(&env, [NightReport {
+        day: 1,
+        mafia_target: Some(victim),
+        doctor_target: Some(victim),
+        sheriff_target: Some(mafia),
+        sheriff_found_mafia: true,
+        vigilante_target: None,
+        serial_killer_target: None,
+    }]));
+}
+
+#[test]
+fn test_night_reports_hidden_by_default() {
+    let (env, client, players) = begun_game(133, 8, 0);
+    run_night(&env, &client, 133, &players, &[PASS_TARGET; 8]);
+    assert_mafia_error(&client.try_get_night_reports(&133), MafiaError::RuleDisabled);
+    assert!(client.get_game(&133).unwrap().night_reports.is_empty());
+}
+
+#[test]
+fn test_kill_source_shown_only_in_open_nights() {
+    let (env, client, _hub) = setup_test();
+    for (session_id, rules) in [(202u32, RULE_OPEN_NIGHT), (203, 0)] {
+        let players = seat_humans(&env, &client, session_id, 8);
+        configure(&client, session_id, &players.get(0).unwrap(), rules);
+        client.begin_game(&session_id, &players.get(0).unwrap());
+        let victim = first_town(&client, session_id);
+        run_night(&env, &client, session_id, &players, &mafia_kill(&client, session_id, victim));
+        let summary = client.get_game_summary(&session_id);
+        assert_eq!(summary.last_killed, Some(victim));
+        assert_eq!(summary.last_kill_source, if rules == 0 { None } else { Some(ROLE_MAFIA) });
+    }
+}
+
+// ============================================================================
+// Result Query Tests
+// ============================================================================
+
+#[test]
+fn test_did_i_win_after_town_win() {
+    let (env, client, players) = begun_game(134, 8, 0);
+    let mafia = slots_with_role(&client, 134, ROLE_MAFIA).get(0).unwrap();
+    let town = first_town(&client, 134);
+    assert_eq!(client.did_i_win(&134, &players.get(town).unwrap()), None);
+    play_town_win(&env, &client, 134, &players);
+    assert_eq!(client.did_i_win(&134, &players.get(town).unwrap()), Some(true));
+    assert_eq!(client.did_i_win(&134, &players.get(mafia).unwrap()), Some(false));
+    assert_eq!(client.did_i_win(&134, &Address::generate(&env)), None);
+}
+
+#[test]
+fn test_did_i_win_for_neutral_serial_killer() {
+    let (env, client, players) = serial_killer_endgame(135, RULE_NEUTRAL_BLOCKS_PARITY, 0);
+    let mafia = slots_with_role(&client, 135, ROLE_MAFIA);
+    let sk = slots_with_role(&client, 135, ROLE_SERIAL_KILLER).get(0).unwrap();
+    let mut targets = [PASS_TARGET; 8];
+    targets[sk as usize] = mafia.get(0).unwrap();
+    run_night(&env, &client, 135, &players, &targets);
+    assert_eq!(client.did_i_win(&135, &players.get(sk).unwrap()), Some(true));
+    assert_eq!(client.did_i_win(&135, &players.get(mafia.get(1).unwrap()).unwrap()), Some(false));
+}
+
+// ============================================================================
+// Anonymous Vote Tests
+// ============================================================================
+
+#[test]
+fn test_anonymous_votes_show_totals_not_voters() {
+    let (_env, client, players) = day_with_votes(136, RULE_ANONYMOUS_VOTES);
+    assert!(client.get_day_votes_live(&136).is_empty());
+    assert_eq!(client.get_day_vote_counts(&136).get(5), Some(3));
+    let game = client.get_game(&136).unwrap();
+    for i in 0..MAX_PLAYERS { assert_eq!(game.slots.get(i).unwrap().action, None); }
+    assert!(game.slots.get(0).unwrap().submitted);
+
+    for i in 3..8u32 { client.submit_action(&136, &players.get(i).unwrap(), &PASS_TARGET); }
+    client.resolve(&136);
+    assert_eq!(client.get_last_tally(&136).get(5), Some(3));
+}
+
+#[test]
+fn test_open_votes_are_attributed() {
+    let (_env, client, _players) = day_with_votes(137, 0);
+    assert_eq!(client.get_day_votes_live(&137).len(), 3);
+    assert_eq!(client.get_day_vote_counts(&137).get(5), Some(3));
+    assert_eq!(client.get_game(&137).unwrap().slots.get(0).unwrap().action, Some(5));
+}
+
+// ============================================================================
+// Invariant Tests
+// ============================================================================
+
+fn invariants_hold(env: &Env, client: &MafiaDuelContractClient, session_id: u32) -> bool {
+    env.as_contract(&client.address, || MafiaDuelContract::check_invariants(env, session_id))
+}
+
+fn corrupt(env: &Env, client: &MafiaDuelContractClient, session_id: u32, f: impl FnOnce(&mut Game)) {
+    let mut game = client.get_game(&session_id).unwrap();
+    f(&mut game);
+    env.as_contract(&client.address, || {
+        env.storage().temporary().set(&DataKey::Game(session_id), &game);
+    });
+}
+
+#[test]
+fn test_invariants_hold_through_a_full_game() {
+    let (env, client, _hub) = setup_test();
+    assert!(!invariants_hold(&env, &client, 138));
+    let players = seat_humans(&env, &client, 138, 8);
+    assert!(invariants_hold(&env, &client, 138));
+    client.begin_game(&138, &players.get(0).unwrap());
+    let mafia = slots_with_role(&client, 138, ROLE_MAFIA);
+    for m in mafia.iter() {
+        commit_all(&env, &client, 138, &players, &[PASS_TARGET; 8]);
+        assert!(invariants_hold(&env, &client, 138));
+        reveal_all(&client, 138, &players, &[PASS_TARGET; 8]);
+        client.resolve(&138);
+        assert!(invariants_hold(&env, &client, 138));
+        run_day(&client, 138, &players, &[m; 8]);
+        assert!(invariants_hold(&env, &client, 138));
+    }
+    assert_eq!(client.get_game(&138).unwrap().phase, PHASE_OVER);
+}
+
+#[test]
+fn test_invariants_catch_corruption() {
+    let (env, client, players) = begun_game(139, 3, 0);
+    assert!(invariants_hold(&env, &client, 139));
+
+    corrupt(&env, &client, 139, |g| g.winner = Some(TEAM_TOWN));
+    assert!(!invariants_hold(&env, &client, 139));
+    corrupt(&env, &client, 139, |g| g.winner = None);
+    assert!(invariants_hold(&env, &client, 139));
+
+    let twin = players.get(1).unwrap();
+    corrupt(&env, &client, 139, |g| {
+        let mut s = g.slots.get(2).unwrap();
+        s.addr = Some(twin);
+        g.slots.set(2, s);
+    });
+    assert!(!invariants_hold(&env, &client, 139));
+}
+
+#[test]
+fn test_invariants_catch_bad_counts() {
+    let (env, client, _) = begun_game(140, 2, 0);
+    corrupt(&env, &client, 140, |g| g.human_count = 5);
+    assert!(!invariants_hold(&env, &client, 140));
+    corrupt(&env, &client, 140, |g| {
+        g.human_count = 2;
+        let mut s = g.slots.get(0).unwrap();
+        s.role = if s.role == ROLE_MAFIA { ROLE_VILLAGER } else { ROLE_MAFIA };
+        g.slots.set(0, s);
+    });
+    assert!(!invariants_hold(&env, &client, 140));
+    corrupt(&env, &client, 140, |g| g.phase = 9);
+    assert!(!invariants_hold(&env, &client, 140));
+}
+
+// ============================================================================
+// Payout Tests
+// ============================================================================
+
+fn payouts_after_town_win(session_id: u32, wager: i128, consolation_bps: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<u32>) {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    client.create_game_with_config(&session_id, &creator, &wager, &GameConfig { consolation_bps, ..default_config() });
+    let mut players = Vec::from_array(&env, [creator]);
+    for _ in 1..8 {
+        let p = Address::generate(&env);
+        client.join_game(&session_id, &p);
+        players.push_back(p);
+    }
+    client.begin_game(&session_id, &players.get(0).unwrap());
+    play_town_win(&env, &client, session_id, &players);
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+    (env, client, mafia)
+}
+
+#[test]
+fn test_payouts_conserve_the_pot() {
+    for (sid, wager, bps) in [(141, 100, 0), (142, 100, 250), (143, 7, 1_000), (144, 999_999, 3_333), (145, 1, 10_000)] {
+        let (_env, client, mafia) = payouts_after_town_win(sid, wager, bps);
+        let payouts = client.get_payouts(&sid);
+        assert_eq!(payouts.len(), 8);
+        let total: i128 = payouts.iter().map(|(_, amount)| amount).sum();
+        assert_eq!(total, wager * 8);
+
+        let game = client.get_game(&sid).unwrap();
+        let to_losers: i128 = payouts.iter()
+            .filter(|(a, _)| mafia.iter().any(|m| game.slots.get(m).unwrap().addr.as_ref() == Some(a)))
+            .map(|(_, amount)| amount)
+            .sum();
+        assert_eq!(to_losers, wager * 8 * bps as i128 / 10_000);
+    }
+}
+
+#[test]
+fn test_payouts_only_after_game_over() {
+    let (_env, client, players) = begun_game(146, 4, 0);
+    assert_mafia_error(&client.try_get_payouts(&146), MafiaError::WrongPhase);
+    let bad = GameConfig { consolation_bps: 10_001, ..default_config() };
+    assert_mafia_error(&client.try_create_game_with_config(&147, &players.get(0).unwrap(), &100, &bad), MafiaError::InvalidConfig);
+}
+
+// ============================================================================
+// Full Sync Tests
+// ============================================================================
+
+#[test]
+fn test_full_sync_matches_public_getters() {
+    let (env, client, players) = begun_game(148, 8, RULE_REVEAL_ON_LYNCH);
+    let victim = first_town(&client, 148);
+    run_night(&env, &client, 148, &players, &mafia_kill(&client, 148, victim));
+    let mafia = slots_with_role(&client, 148, ROLE_MAFIA).get(0).unwrap();
+    run_day(&client, 148, &players, &[mafia; 8]);
+    run_night(&env, &client, 148, &players, &[PASS_TARGET; 8]);
+
+    let sync = client.full_sync(&148);
+    assert_eq!(sync.summary, client.get_game_summary(&148));
+    assert_eq!(sync.graveyard, client.get_graveyard(&148));
+    assert_eq!(sync.night_log, client.get_game(&148).unwrap().night_log);
+    assert_eq!(sync.night_log.len(), 2);
+    assert_eq!(sync.last_tally, client.get_last_tally(&148));
+    for i in 0..MAX_PLAYERS {
+        let expected = if i == mafia { ROLE_MAFIA } else { ROLE_NONE };
+        assert_eq!(sync.known_roles.get(i), Some(expected));
+    }
+}
+
+#[test]
+fn test_full_sync_holds_back_unannounced_deaths() {
+    let (env, client, players) = begun_game(149, 8, RULE_MORNING_ANNOUNCE | RULE_REVEAL_ON_NIGHTKILL);
+    let victim = first_town(&client, 149);
+    run_night(&env, &client, 149, &players, &mafia_kill(&client, 149, victim));
+    let sync = client.full_sync(&149);
+    assert!(sync.graveyard.is_empty());
+    assert!(sync.summary.alive.contains(victim));
+    assert_eq!(sync.known_roles.get(victim), Some(ROLE_NONE));
+
+    client.announce(&149, &players.get(0).unwrap());
+    let sync = client.full_sync(&149);
+    assert_eq!(sync.graveyard.len(), 1);
+    assert_ne!(sync.known_roles.get(victim), Some(ROLE_NONE));
+}
+
+// ============================================================================
+// Sheriff Death Timing Tests
+// ============================================================================
+
+/// The sheriff checks a mafia on the night the mafia kill them.
+fn sheriff_dies_investigating(session_id: u32, rules: u32) -> (MafiaDuelContractClient<'static>, u32) {
+    let (env, client, players) = begun_game(session_id, 8, rules);
+    let sheriff = slots_with_role(&client, session_id, ROLE_SHERIFF).get(0).unwrap();
+    let mafia = slots_with_role(&client, session_id, ROLE_MAFIA).get(0).unwrap();
+    let mut targets = mafia_kill(&client, session_id, sheriff);
+    targets[sheriff as usize] = mafia;
+    run_night(&env, &client, session_id, &players, &targets);
+    assert!(!client.get_game(&session_id).unwrap().slots.get(sheriff).unwrap().alive);
+    (client, mafia)
+}
+
+#[test]
+fn test_killed_sheriff_result_stands_by_default() {
+    let (client, mafia) = sheriff_dies_investigating(150, 0);
+    let game = client.get_game(&150).unwrap();
+    assert_eq!(game.last_investigated, Some(mafia));
+    assert!(game.invest_is_mafia);
+}
+
+#[test]
+fn test_killed_sheriff_result_voided_when_strict() {
+    let (client, _mafia) = sheriff_dies_investigating(151, RULE_STRICT_SHERIFF);
+    let game = client.get_game(&151).unwrap();
+    assert_eq!(game.last_investigated, None);
+    assert!(!game.invest_is_mafia);
+}
+
+// ============================================================================
+// Fixed Seed Tests
+// ============================================================================
+
+/// One human who always passes, alongside bots, for up to `rounds` nights and days.
+fn seeded_game(env: &Env, client: &MafiaDuelContractClient, session_id: u32, seed: Option<BytesN<32>>, rounds: u32) -> Game {
+    let players = seat_humans(env, client, session_id, 1);
+    client.set_rng_seed(&session_id, &players.get(0).unwrap(), &seed);
+    client.begin_game(&session_id, &players.get(0).unwrap());
+    for _ in 0..rounds {
+        if client.get_game(&session_id).unwrap().phase == PHASE_OVER { break; }
+        if !client.get_game(&session_id).unwrap().slots.get(0).unwrap().alive { break; }
+        run_night(env, client, session_id, &players, &[PASS_TARGET; 8]);
+        if client.get_game(&session_id).unwrap().phase == PHASE_OVER { break; }
+        if !client.get_game(&session_id).unwrap().slots.get(0).unwrap().alive { break; }
+        run_day(client, session_id, &players, &[PASS_TARGET; 8]);
+    }
+    client.get_game(&session_id).unwrap()
+}
+
+#[test]
+fn test_same_seed_replays_identically() {
+    let (env, client, _hub) = setup_test();
+    let seed = Some(BytesN::from_array(&env, &[42u8; 32]));
+    let a = seeded_game(&env, &client, 152, seed.clone(), 3);
+    let b = seeded_game(&env, &client, 153, seed, 3);
+    assert!(!a.graveyard.is_empty());
+    assert_eq!(a.graveyard, b.graveyard);
+    assert_eq!((a.phase, a.day, a.winner), (b.phase, b.day, b.winner));
+    for i in 0..MAX_PLAYERS {
+        assert_eq!(a.slots.get(i).unwrap().role, b.slots.get(i).unwrap().role);
+    }
+    assert_eq!(client.get_role_seed(&152), client.get_role_seed(&153));
+}
+
+#[test]
+fn test_unseeded_games_derive_from_session() {
+    let (env, client, _hub) = setup_test();
+    seeded_game(&env, &client, 154, None, 0);
+    seeded_game(&env, &client, 155, None, 0);
+    assert_ne!(client.get_role_seed(&154), client.get_role_seed(&155));
+    let game = client.get_game(&154).unwrap();
+    assert_mafia_error(&client.try_set_rng_seed(&154, &game.creator, &None), MafiaError::WrongPhase);
+}
+
+// ============================================================================
+// Reveal Progress Tests
+// ============================================================================
+
+#[test]
+fn test_reveal_progress_counts_partial_reveals() {
+    let (env, client, players) = begun_game(156, 4, 0);
+    assert_mafia_error(&client.try_reveal_progress(&156), MafiaError::WrongPhase);
+    commit_all(&env, &client, 156, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.reveal_progress(&156), (0, 4));
+    for i in 0..3u32 {
+        client.reveal_action(&156, &players.get(i).unwrap(), &PASS_TARGET, &(i as u64));
+    }
+    assert_eq!(client.reveal_progress(&156), (3, 4));
+}
+
+#[test]
+fn test_interleaved_reveals_each_keep_their_own_slot() {
+    let (env, client, players) = begun_game(201, 8, 0);
+    let victim = slots_with_role(&client, 201, ROLE_VILLAGER).get(0).unwrap();
+    let mut targets = mafia_kill(&client, 201, victim);
+    targets[slots_with_role(&client, 201, ROLE_DOCTOR).get(0).unwrap() as usize] = victim;
+    targets[slots_with_role(&client, 201, ROLE_SHERIFF).get(0).unwrap() as usize] = slots_with_role(&client, 201, ROLE_MAFIA).get(0).unwrap();
+
+    // Commits land in reverse seat order, the last one flipping the game to reveal; reveals then
+    // arrive scrambled, each applied against whatever the previous one stored.
+    for i in (0..8u32).rev() {
+        client.submit_commitment(&201, &players.get(i).unwrap(), &commitment(&env, targets[i as usize], i as u64));
+    }
+    let order = [5u32, 0, 7, 2, 6, 1, 4, 3];
+    for (n, &i) in order.iter().enumerate() {
+        client.reveal_action(&201, &players.get(i).unwrap(), &targets[i as usize], &(i as u64));
+        assert_eq!(client.reveal_progress(&201), (n as u32 + 1, 8));
+    }
+
+    let game = client.get_game(&201).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_REVEAL);
+    for i in 0..8u32 {
+        let s = game.slots.get(i).unwrap();
+        let t = targets[i as usize];
+        assert!(s.submitted);
+        assert_eq!(s.commitment, Some(commitment(&env, t, i as u64)));
+        assert_eq!(s.action, if t == PASS_TARGET { None } else { Some(t) });
+    }
+}
+
+// ============================================================================
+// AFK Botify Tests
+// ============================================================================
+
+/// Slot 0 plays its part of the current phase (PASS), then the phase is forced through.
+fn force_phase(env: &Env, client: &MafiaDuelContractClient, session_id: u32, creator: &Address) {
+    let game = client.get_game(&session_id).unwrap();
+    match game.phase {
+        PHASE_NIGHT_COMMIT => client.submit_commitment(&session_id, creator, &commitment(env, PASS_TARGET, 0)),
+        PHASE_NIGHT_REVEAL => client.reveal_action(&session_id, creator, &PASS_TARGET, &0),
+        _ => client.submit_action(&session_id, creator, &PASS_TARGET),
+    }
+    advance_ledgers(env, 11);
+    client.resolve(&session_id);
+}
+
+#[test]
+fn test_silent_human_is_botified_then_reclaims_seat() {
+    let (env, client, players) = begun_game_with(157, 2, GameConfig { rules: RULE_AFK_BOTIFY, grace_ledgers: 10, ..default_config() });
+    let creator = players.get(0).unwrap();
+
+    // Commit, reveal (nothing owed without a commitment), day, commit.
+    for _ in 0..4 { force_phase(&env, &client, 157, &creator); }
+    let silent = client.get_game(&157).unwrap().slots.get(1).unwrap();
+    assert_eq!(silent.missed_phases, AFK_PHASE_LIMIT);
+    assert!(silent.afk);
+
+    // The game no longer waits out slot 1's grace.
+    client.reveal_action(&157, &creator, &PASS_TARGET, &0);
+    client.resolve(&157);
+    assert_eq!(client.get_game(&157).unwrap().phase, PHASE_DAY);
+    assert!(client.predict_ai_action(&157, &1).is_some());
+
+    // Acting again hands the seat back.
+    client.submit_action(&157, &creator, &PASS_TARGET);
+    client.submit_action(&157, &players.get(1).unwrap(), &PASS_TARGET);
+    client.resolve(&157);
+    let back = client.get_game(&157).unwrap().slots.get(1).unwrap();
+    assert_eq!((back.missed_phases, back.afk), (0, false));
+}
+
+// ============================================================================
+// Session Reservation Tests
+// ============================================================================
+
+#[test]
+fn test_reserve_session_skips_taken_ids() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    client.create_game(&1, &creator, &100);
+    let first = client.reserve_session(&creator);
+    let second = client.reserve_session(&creator);
+    assert_eq!((first, second), (0, 2));
+    client.create_game(&first, &creator, &100);
+    client.create_game(&second, &creator, &100);
+    assert_eq!(client.reserve_session(&creator), 3);
+}
+
+#[test]
+fn test_reserved_session_held_for_its_creator() {
+    let (env, client, _hub) = setup_test();
+    let (holder, other) = (Address::generate(&env), Address::generate(&env));
+    let id = client.reserve_session(&holder);
+    assert_mafia_error(&client.try_create_game(&id, &other, &100), MafiaError::SessionExists);
+    assert_mafia_error(&client.try_create_bracket(&id, &2, &other, &100, &default_config()), MafiaError::SessionExists);
+    let pair = Vec::from_array(&env, [other.clone(), Address::generate(&env)]);
+    assert_mafia_error(&client.try_create_match(&id, &pair, &3, &100), MafiaError::SessionExists);
+    assert_eq!(client.session_status(&id), STATUS_NONEXISTENT);
+
+    client.create_game(&id, &holder, &100);
+    assert_eq!(client.get_game(&id).unwrap().creator, holder);
+}
+
+// ============================================================================
+// Match Tests
+// ============================================================================
+
+/// Play out the current round of `match_id` so that match player `winner` takes it: they are
+/// the only town left standing once the other human (rigged as the lone mafia) is dead.
+fn win_round(env: &Env, client: &MafiaDuelContractClient, match_id: u32, players: &Vec<Address>, winner: u32) {
+    let session_id = rig_round(env, client, match_id, winner);
+    run_night(env, client, session_id, players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&session_id).unwrap().winner, Some(TEAM_TOWN));
+}
+
+/// Begin the match's current round with only player `1 - winner` mafia, already dead, so the
+/// next resolve hands the round to `winner`. Returns the round's session_id.
+fn rig_round(env: &Env, client: &MafiaDuelContractClient, match_id: u32, winner: u32) -> u32 {
+    let m = client.get_match(&match_id).unwrap();
+    let session_id = m.sessions.last().unwrap();
+    client.begin_game(&session_id, &m.players.get(0).unwrap());
+    corrupt(env, client, session_id, |game| {
+        for i in 0..MAX_PLAYERS {
+            let mut s = game.slots.get(i).unwrap();
+            if i == 1 - winner {
+                s.role  = ROLE_MAFIA;
+                s.alive = false;
+            } else if s.role == ROLE_MAFIA {
+                s.role = ROLE_VILLAGER;
+            }
+            game.slots.set(i, s);
+        }
+    });
+    session_id
+}
+
+fn match_players(env: &Env) -> Vec<Address> {
+    Vec::from_array(env, [Address::generate(env), Address::generate(env)])
+}
+
+#[test]
+fn test_match_sweep_two_nil() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = match_players(&env);
+    let first = client.create_match(&7, &players, &3, &100);
+    assert_eq!(hub_client.started_players(&7), Some((players.get(0).unwrap(), players.get(1).unwrap())));
+    assert_mafia_error(&client.try_create_match(&7, &players, &3, &100), MafiaError::SessionExists);
+
+    win_round(&env, &client, 7, &players, 0);
+    let m = client.get_match(&7).unwrap();
+    assert_eq!((m.wins.get(0).unwrap(), m.wins.get(1).unwrap(), m.over), (1, 0, false));
+    let second = m.sessions.get(1).unwrap();
+    assert_ne!(second, first);
+    assert_eq!(client.get_game(&second).unwrap().phase, PHASE_LOBBY);
+    assert_eq!(hub_client.player1_won(&7), None);
+    assert_eq!(hub_client.started_players(&first), None);
+
+    win_round(&env, &client, 7, &players, 0);
+    let m = client.get_match(&7).unwrap();
+    assert_eq!((m.wins.get(0).unwrap(), m.wins.get(1).unwrap(), m.over), (2, 0, true));
+    assert_eq!(m.sessions.len(), 2);
+    assert_eq!(hub_client.player1_won(&7), Some(true));
+    assert_eq!(hub_client.player1_won(&second), None);
+}
+
+#[test]
+fn test_match_decided_in_third_round() {
+    let (env, client, hub) = setup_test();
+    let hub_client = MockGameHubClient::new(&env, &hub);
+    let players = match_players(&env);
+    assert_mafia_error(&client.try_create_match(&8, &players, &(MAX_MATCH_ROUNDS + 1), &100), MafiaError::InvalidConfig);
+    client.create_match(&8, &players, &3, &100);
+    for winner in [0, 1, 1] { win_round(&env, &client, 8, &players, winner); }
+    let m = client.get_match(&8).unwrap();
+    assert_eq!((m.wins.get(0).unwrap(), m.wins.get(1).unwrap(), m.over), (1, 2, true));
+    assert_eq!(m.sessions.len(), 3);
+    assert_eq!(hub_client.player1_won(&8), Some(false));
+}
+
+#[test]
+fn test_match_and_session_ids_never_overlap() {
+    let (env, client, _hub) = setup_test();
+    let players = match_players(&env);
+    let first = client.create_match(&0, &players, &3, &100);
+    assert_ne!(first, 0);
+    assert_mafia_error(&client.try_create_game(&0, &players.get(0).unwrap(), &100), MafiaError::SessionExists);
+    client.create_game(&20, &players.get(0).unwrap(), &100);
+    assert_mafia_error(&client.try_create_match(&20, &players, &3, &100), MafiaError::SessionExists);
+}
+
+#[test]
+fn test_match_round_holds_until_next_lobby_can_open() {
+    let (env, client, _hub) = setup_test();
+    let players = match_players(&env);
+    client.create_match(&12, &players, &3, &100);
+    let session_id = rig_round(&env, &client, 12, 0);
+    commit_all(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    reveal_all(&client, session_id, &players, &[PASS_TARGET; 8]);
+
+    // Round two's lobby would break the new limits, so the round can't close yet.
+    client.set_wager_limits(&200, &500);
+    assert_mafia_error(&client.try_resolve(&session_id), MafiaError::InvalidWager);
+    assert_eq!(client.get_game(&session_id).unwrap().phase, PHASE_NIGHT_REVEAL);
+    assert_eq!(client.get_match(&12).unwrap().sessions.len(), 1);
+
+    client.set_wager_limits(&0, &500);
+    client.resolve(&session_id);
+    let m = client.get_match(&12).unwrap();
+    assert_eq!((m.wins.get(0).unwrap(), m.sessions.len()), (1, 2));
+}
+
+#[test]
+fn test_match_round_seats_cannot_swap() {
+    let (env, client, _hub) = setup_test();
+    let players = match_players(&env);
+    let first = client.create_match(&13, &players, &3, &100);
+    let fresh = Address::generate(&env);
+    assert_mafia_error(&client.try_swap_address(&first, &players.get(1).unwrap(), &fresh), MafiaError::SeatLocked);
+}
+
+// ============================================================================
+// Role Stats Tests
+// ============================================================================
+
+#[test]
+fn test_role_stats_accumulate_across_games() {
+    let (env, client, _hub) = setup_test();
+    let zeros = Vec::from_array(&env, [0u32; ROLE_COUNT as usize]);
+    assert_eq!(client.get_role_stats(), RoleStats { games: zeros.clone(), wins: zeros });
+
+    for (session_id, town_wins) in [(158u32, true), (159, false), (160, true)] {
+        let players = seat_humans(&env, &client, session_id, 8);
+        client.begin_game(&session_id, &players.get(0).unwrap());
+        if town_wins { play_town_win(&env, &client, session_id, &players); }
+        else { play_mafia_win(&env, &client, session_id, &players); }
+    }
+    // Mafia, villager, doctor, sheriff are dealt every time; the optional roles never are.
+    let stats = client.get_role_stats();
+    assert_eq!(stats.games, Vec::from_array(&env, [3, 3, 3, 3, 0, 0, 0]));
+    assert_eq!(stats.wins, Vec::from_array(&env, [1, 2, 2, 2, 0, 0, 0]));
+}
+
+// ============================================================================
+// Overflow Tests
+// ============================================================================
+
+#[test]
+fn test_wager_too_large_for_a_pot_is_rejected() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    assert_mafia_error(&client.try_create_game(&161, &creator, &i128::MAX), MafiaError::Overflow);
+    let too_big = i128::MAX / MAX_PLAYERS as i128 + 1;
+    let config = default_config();
+    assert_mafia_error(&client.try_create_game_with_config(&161, &creator, &too_big, &config), MafiaError::Overflow);
+    assert!(client.get_game(&161).is_none());
+}
+
+#[test]
+fn test_largest_wager_settles_without_overflow() {
+    let wager = i128::MAX / MAX_PLAYERS as i128;
+    let (env, client, _hub) = payouts_after_town_win(162, wager, 3_333);
+    let total = client.get_payouts(&162).iter().fold(0i128, |sum, (_, amount)| sum.checked_add(amount).unwrap());
+    assert_eq!(total, wager * MAX_PLAYERS as i128);
+
+    // Role points scale the largest wager without overflowing either.
+    let players = seat_humans(&env, &client, 163, 2);
+    client.configure_game(&163, &players.get(0).unwrap(), &GameConfig { rules: RULE_ROLE_POINTS, ..default_config() });
+    corrupt(&env, &client, 163, |game| game.wager = wager);
+    client.begin_game(&163, &players.get(0).unwrap());
+}
+
+#[test]
+fn test_saturated_counters_stay_at_max() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 164, 8);
+    let maxed = Vec::from_array(&env, [u32::MAX; ROLE_COUNT as usize]);
+    let creator = players.get(0).unwrap();
+    env.as_contract(&client.address, || {
+        env.storage().persistent().set(&DataKey::RoleStats, &RoleStats { games: maxed.clone(), wins: maxed.clone() });
+        let stats = PlayerStats {
+            games_played: u32::MAX, games_won: u32::MAX, mafia_wins: u32::MAX, town_wins: u32::MAX,
+            games_survived: u32::MAX, roles_played: maxed.clone(),
+        };
+        env.storage().persistent().set(&DataKey::Stats(creator.clone()), &stats);
+    });
+    client.begin_game(&164, &creator);
+    play_town_win(&env, &client, 164, &players);
+    assert_eq!(client.get_role_stats().games, maxed);
+    let stats = client.get_stats(&creator);
+    assert_eq!((stats.games_played, stats.games_won), (u32::MAX, u32::MAX));
+}
+
+// ============================================================================
+// Finalize Tests
+// ============================================================================
+
+#[test]
+fn test_finalize_compacts_a_finished_game() {
+    let (env, client, players) = begun_game(165, 8, 0);
+    assert_mafia_error(&client.try_finalize(&165), MafiaError::WrongPhase);
+    play_town_win(&env, &client, 165, &players);
+    let game = client.get_game(&165).unwrap();
+
+    let fin = client.finalize(&165);
+    assert_eq!(fin.winner, Some(TEAM_TOWN));
+    assert_eq!(fin.day, game.day);
+    for i in 0..MAX_PLAYERS {
+        assert_eq!(fin.roles.get(i).unwrap(), game.slots.get(i).unwrap().role);
+        assert_eq!(fin.seats.get(i).unwrap().addr, game.slots.get(i).unwrap().addr);
+    }
+    assert!(client.get_game(&165).is_none());
+    assert_eq!(client.get_finalized(&165), Some(fin.clone()));
+    assert_eq!(client.finalize(&165), fin);
+
+    // Reads fall back to the summary, and the id stays taken.
+    assert_eq!(client.session_status(&165), STATUS_OVER);
+    let town = (0..MAX_PLAYERS).find(|&i| fin.roles.get(i).unwrap() != ROLE_MAFIA).unwrap();
+    let loser = (0..MAX_PLAYERS).find(|&i| fin.roles.get(i).unwrap() == ROLE_MAFIA).unwrap();
+    assert_eq!(client.did_i_win(&165, &players.get(town).unwrap()), Some(true));
+    assert_eq!(client.did_i_win(&165, &players.get(loser).unwrap()), Some(false));
+    assert_mafia_error(&client.try_create_game(&165, &players.get(0).unwrap(), &100), MafiaError::SessionExists);
+}
+
+#[test]
+fn test_post_game_reads_survive_finalize() {
+    let (env, client, players) = begun_game(228, 8, RULE_TRANSCRIPT);
+    play_town_win(&env, &client, 228, &players);
+
+    let transcript = client.get_reveal_transcript(&228);
+    let payouts = client.get_payouts(&228);
+    let mafia = client.get_team_members(&228, &TEAM_MAFIA);
+    let town = client.get_team_members(&228, &TEAM_TOWN);
+    let graveyard = client.get_graveyard(&228);
+    let sync = client.full_sync(&228);
+    client.finalize(&228);
+    assert!(client.get_game(&228).is_none());
+
+    assert!(!transcript.is_empty());
+    assert_eq!(client.get_reveal_transcript(&228), transcript);
+    assert_eq!(client.get_payouts(&228), payouts);
+    assert_eq!(client.get_team_members(&228, &TEAM_MAFIA), mafia);
+    assert_eq!(client.get_team_members(&228, &TEAM_TOWN), town);
+    assert_eq!(client.get_graveyard(&228), graveyard);
+    let after = client.full_sync(&228);
+    assert_eq!(after.graveyard, graveyard);
+    assert_eq!(after.known_roles, sync.known_roles);
+    assert_eq!(after.summary.winner, Some(TEAM_TOWN));
+    assert_eq!(after.summary.phase, PHASE_OVER);
+
+    // The last mafioso was lynched on the final day, so their window is still open.
+    let lynched = graveyard.last().unwrap().slot;
+    let words = Bytes::from_slice(&env, b"gg");
+    client.leave_last_words(&228, &players.get(lynched).unwrap(), &words);
+    let graves = client.get_graveyard(&228);
+    assert_eq!(graves.last().unwrap().last_words, Some(words.clone()));
+    assert_eq!(client.full_sync(&228).graveyard, graves);
+    let history: BytesN<32> = env.crypto().sha256(&graves.clone().to_xdr(&env)).into();
+    assert_eq!(client.get_finalized(&228).unwrap().history, history);
+    assert_mafia_error(&client.try_leave_last_words(&228, &players.get(lynched).unwrap(), &words), MafiaError::AlreadyActed);
+}
+
+// ============================================================================
+// Single Player Tests
+// ============================================================================
+
+#[test]
+fn test_solo_start_allowed_by_default() {
+    let (env, client, _hub) = setup_test();
+    assert!(client.get_single_player_allowed());
+    let players = seat_humans(&env, &client, 166, 1);
+    client.begin_game(&166, &players.get(0).unwrap());
+    assert_eq!(client.get_game(&166).unwrap().phase, PHASE_NIGHT_COMMIT);
+}
+
+#[test]
+fn test_solo_start_forbidden_for_multiplayer_only() {
+    let (env, client, _hub) = setup_test();
+    client.set_single_player_allowed(&false);
+    let players = seat_humans(&env, &client, 167, 1);
+    let creator = players.get(0).unwrap();
+    assert_mafia_error(&client.try_begin_game(&167, &creator), MafiaError::NotEnoughPlayers);
+    client.join_game(&167, &Address::generate(&env));
+    client.begin_game(&167, &creator);
+    assert_eq!(client.get_game(&167).unwrap().phase, PHASE_NIGHT_COMMIT);
+}
+
+// ============================================================================
+// Closed Window Tests
+// ============================================================================
+
+#[test]
+fn test_late_commit_and_reveal_report_closed_window() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 168, 2);
+    let (creator, slow) = (players.get(0).unwrap(), players.get(1).unwrap());
+    client.configure_game(&168, &creator, &GameConfig { grace_ledgers: 10, ..default_config() });
+    client.begin_game(&168, &creator);
+
+    client.submit_commitment(&168, &creator, &commitment(&env, PASS_TARGET, 0));
+    advance_ledgers(&env, 11);
+    client.resolve(&168);
+    let late = client.try_submit_commitment(&168, &slow, &commitment(&env, PASS_TARGET, 1));
+    assert_mafia_error(&late, MafiaError::CommitWindowClosed);
+
+    client.reveal_action(&168, &creator, &PASS_TARGET, &0);
+    client.resolve(&168);
+    assert_eq!(client.get_game(&168).unwrap().phase, PHASE_DAY);
+    let late = client.try_reveal_action(&168, &creator, &PASS_TARGET, &0);
+    assert_mafia_error(&late, MafiaError::RevealWindowClosed);
+}
+
+// ============================================================================
+// Ability Charge Tests
+// ============================================================================
+
+#[test]
+fn test_watcher_charges_deplete_and_recharge() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 169, 8);
+    let creator = players.get(0).unwrap();
+    let bad = GameConfig { rules: RULE_WATCHER, ability_charges: 0, ..default_config() };
+    assert_mafia_error(&client.try_configure_game(&169, &creator, &bad), MafiaError::InvalidConfig);
+    let config = GameConfig { rules: RULE_WATCHER, ability_charges: 2, recharge_every: 2, ..default_config() };
+    client.configure_game(&169, &creator, &config);
+    client.begin_game(&169, &creator);
+
+    let watcher = slots_with_role(&client, 169, ROLE_VILLAGER).get(0).unwrap();
+    let addr = players.get(watcher).unwrap();
+    let mut targets = [PASS_TARGET; 8];
+    targets[watcher as usize] = slots_with_role(&client, 169, ROLE_MAFIA).get(0).unwrap();
+    for left in [2, 1] {
+        assert_eq!(client.decision_space(&169, &addr).abilities_left, left);
+        run_night(&env, &client, 169, &players, &targets);
+        assert_eq!(client.decision_space(&169, &addr).abilities_left, left - 1);
+        run_day(&client, 169, &players, &[PASS_TARGET; 8]);
+    }
+    // Night 3 is the first refill of a two-day schedule.
+    assert_eq!(client.get_game(&169).unwrap().day, 3);
+    assert_eq!(client.decision_space(&169, &addr).abilities_left, 2);
+    run_night(&env, &client, 169, &players, &targets);
+    assert_eq!(client.get_game(&169).unwrap().slots.get(watcher).unwrap().ability_charges, 1);
+}
+
+// ============================================================================
+// Resolvable Sessions Tests
+// ============================================================================
+
+#[test]
+fn test_resolvable_sessions_lists_ready_games() {
+    let (env, client, _hub) = setup_test();
+    // A finished game leaves the index; a lobby never joins it.
+    let done = seat_humans(&env, &client, 170, 8);
+    client.begin_game(&170, &done.get(0).unwrap());
+    play_town_win(&env, &client, 170, &done);
+    seat_humans(&env, &client, 171, 1);
+    assert_eq!(client.active_session_count(), 0);
+
+    for (session_id, grace) in [(172u32, 10u32), (173, 1_000)] {
+        let players = seat_humans(&env, &client, session_id, 2);
+        let creator = players.get(0).unwrap();
+        client.configure_game(&session_id, &creator, &GameConfig { grace_ledgers: grace, ..default_config() });
+        client.begin_game(&session_id, &creator);
+    }
+    let solo = seat_humans(&env, &client, 174, 1);
+    client.begin_game(&174, &solo.get(0).unwrap());
+    assert_eq!(client.active_session_count(), 3);
+    assert!(client.resolvable_sessions(&0, &MAX_RESOLVE_SCAN).is_empty());
+
+    // 172's grace runs out; 174's human commits, opening a reveal phase nobody has to wait on.
+    advance_ledgers(&env, 11);
+    client.submit_commitment(&174, &solo.get(0).unwrap(), &commitment(&env, PASS_TARGET, 0));
+    assert_eq!(client.resolvable_sessions(&0, &MAX_RESOLVE_SCAN), Vec::from_array(&env, [172u32, 174]));
+    assert_eq!(client.resolvable_sessions(&1, &1), Vec::new(&env));
+    assert_eq!(client.resolvable_sessions(&2, &5), Vec::from_array(&env, [174u32]));
+    assert_eq!(client.resolvable_sessions(&9, &5), Vec::new(&env));
+}
+
+#[test]
+fn test_prune_drops_lapsed_active_sessions() {
+    let (env, client, _hub) = setup_test();
+    env.ledger().with_mut(|l| { l.min_temp_entry_ttl = 16; l.min_persistent_entry_ttl = 16; });
+    let mut solos = Vec::new(&env);
+    for session_id in [231u32, 232, 233] {
+        let solo = seat_humans(&env, &client, session_id, 1);
+        client.begin_game(&session_id, &solo.get(0).unwrap());
+        solos.push_back(solo.get(0).unwrap());
+    }
+    // Only 232 is touched before the others lapse.
+    advance_ledgers(&env, GAME_TTL_LEDGERS - 10);
+    client.submit_commitment(&232, &solos.get(1).unwrap(), &commitment(&env, PASS_TARGET, 0));
+    advance_ledgers(&env, 20);
+    assert_eq!(client.active_session_count(), 3);
+    assert_eq!(client.resolvable_sessions(&0, &MAX_RESOLVE_SCAN), Vec::from_array(&env, [232u32]));
+
+    assert_eq!(client.prune_active_sessions(&0, &MAX_RESOLVE_SCAN), 2);
+    assert_eq!(client.active_session_count(), 1);
+    assert_eq!(client.resolvable_sessions(&0, &MAX_RESOLVE_SCAN), Vec::from_array(&env, [232u32]));
+    assert_eq!(client.prune_active_sessions(&0, &MAX_RESOLVE_SCAN), 0);
+}
+
+// ============================================================================
+// Mafia Team Tests
+// ============================================================================
+
+#[test]
+fn test_mafia_sees_partners_unless_anonymous() {
+    let (env, client, _hub) = setup_test();
+    let mut players = Vec::new(&env);
+    for (session_id, rules) in [(175u32, 0u32), (176, RULE_MAFIA_ANONYMOUS)] {
+        players = seat_humans(&env, &client, session_id, 8);
+        configure(&client, session_id, &players.get(0).unwrap(), rules);
+        assert_mafia_error(&client.try_get_mafia_team(&session_id, &players.get(0).unwrap()), MafiaError::WrongPhase);
+        client.begin_game(&session_id, &players.get(0).unwrap());
+        let mafia = slots_with_role(&client, session_id, ROLE_MAFIA);
+        let (m1, m2) = (mafia.get(0).unwrap(), mafia.get(1).unwrap());
+        let seen = client.get_mafia_team(&session_id, &players.get(m1).unwrap());
+        if rules == 0 {
+            assert_eq!(seen, Vec::from_array(&env, [m2]));
+        } else {
+            assert!(seen.is_empty());
+        }
+        let town = first_town(&client, session_id);
+        assert!(client.get_mafia_team(&session_id, &players.get(town).unwrap()).is_empty());
+    }
+
+    // Blind mafia may hit a partner; refusing the target would reveal them.
+    let mafia = slots_with_role(&client, 176, ROLE_MAFIA);
+    let (m1, m2) = (mafia.get(0).unwrap(), mafia.get(1).unwrap());
+    let mut targets = [PASS_TARGET; 8];
+    targets[m1 as usize] = m2;
+    commit_all(&env, &client, 176, &players, &targets);
+    client.reveal_action(&176, &players.get(m1).unwrap(), &m2, &(m1 as u64));
+}
+
+// ============================================================================
+// Expired Game Tests
+// ============================================================================
+
+#[test]
+fn test_lapsed_game_reports_expired_not_missing() {
+    let (env, client, _hub) = setup_test();
+    env.ledger().with_mut(|l| { l.min_temp_entry_ttl = 16; l.min_persistent_entry_ttl = 16; });
+    let players = seat_humans(&env, &client, 177, 2);
+    // Joins keep the lobby alive well past the marker's first TTL; then the game itself lapses.
+    for _ in 0..3 {
+        advance_ledgers(&env, GAME_TTL_LEDGERS - 10);
+        client.join_game(&177, &Address::generate(&env));
+    }
+    env.as_contract(&client.address, || {
+        let storage = env.storage();
+        assert!(storage.persistent().get_ttl(&DataKey::Created(177)) > storage.temporary().get_ttl(&DataKey::Game(177)));
+    });
+    advance_ledgers(&env, GAME_TTL_LEDGERS + 20);
+    assert!(client.get_game(&177).is_none());
+    let joiner = Address::generate(&env);
+    assert_mafia_error(&client.try_join_game(&177, &joiner), MafiaError::GameExpired);
+    assert_mafia_error(&client.try_begin_game(&177, &players.get(0).unwrap()), MafiaError::GameExpired);
+    assert_mafia_error(&client.try_join_game(&178, &joiner), MafiaError::GameNotFound);
+}
+
+// ============================================================================
+// Doctor Exposure Tests
+// ============================================================================
+
+/// Night one of a seeded RULE_DOCTOR_EXPOSURE game: the doctor protects the first town slot and
+/// the mafia either pass or attack that same slot. Returns (doctor, doctor_exposed).
+fn doctor_night(env: &Env, client: &MafiaDuelContractClient, session_id: u32, seed: u8, attack: bool) -> (u32, Option<u32>) {
+    let players = seat_humans(env, client, session_id, 8);
+    let creator = players.get(0).unwrap();
+    configure(client, session_id, &creator, RULE_DOCTOR_EXPOSURE);
+    client.set_rng_seed(&session_id, &creator, &Some(BytesN::from_array(env, &[seed; 32])));
+    client.begin_game(&session_id, &creator);
+    let doctor = slots_with_role(client, session_id, ROLE_DOCTOR).get(0).unwrap();
+    let patient = first_town(client, session_id);
+    let mut targets = if attack { mafia_kill(client, session_id, patient) } else { [PASS_TARGET; 8] };
+    targets[doctor as usize] = patient;
+    run_night(env, client, session_id, &players, &targets);
+    (doctor, client.get_game(&session_id).unwrap().doctor_exposed)
+}
+
+#[test]
+fn test_wasted_save_exposes_doctor_under_fixed_seed() {
+    let (env, client, _hub) = setup_test();
+    // Seeds 1-3 roll above DOCTOR_EXPOSE_PCT; seed 4 rolls under it.
+    for (session_id, seed) in [(179u32, 1u8), (180, 2), (181, 3)] {
+        assert_eq!(doctor_night(&env, &client, session_id, seed, false).1, None);
+    }
+    let (doctor, exposed) = doctor_night(&env, &client, 182, 4, false);
+    assert_eq!(exposed, Some(doctor));
+}
+
+#[test]
+fn test_successful_save_never_exposes_doctor() {
+    let (env, client, _hub) = setup_test();
+    for seed in 1..=8u8 {
+        let session_id = 182 + seed as u32;
+        assert_eq!(doctor_night(&env, &client, session_id, seed, true).1, None);
+        assert!(client.get_game(&session_id).unwrap().last_saved);
+    }
+}
+
+#[test]
+fn test_doctor_feedback_reports_whether_an_attack_came() {
+    let (env, client, _hub) = setup_test();
+    for (session_id, attack) in [(199u32, false), (200, true)] {
+        let (doctor, _) = doctor_night(&env, &client, session_id, 1, attack);
+        let slots = client.get_game(&session_id).unwrap().slots;
+        let addr = |i: u32| slots.get(i).unwrap().addr.unwrap();
+        assert_eq!(client.get_doctor_feedback(&session_id, &addr(doctor)), Some(attack));
+        assert_eq!(client.get_doctor_feedback(&session_id, &addr((doctor + 1) % MAX_PLAYERS)), None);
+    }
+}
+
+// ============================================================================
+// Personal Night Preview Tests
+// ============================================================================
+
+#[test]
+fn test_my_night_preview_shows_only_own_action() {
+    let (env, client, players) = begun_game(191, 8, 0);
+    let sheriff = slots_with_role(&client, 191, ROLE_SHERIFF).get(0).unwrap();
+    let suspect = slots_with_role(&client, 191, ROLE_MAFIA).get(0).unwrap();
+    let villager = slots_with_role(&client, 191, ROLE_VILLAGER).get(0).unwrap();
+    let (sheriff_addr, villager_addr) = (players.get(sheriff).unwrap(), players.get(villager).unwrap());
+    assert_mafia_error(&client.try_my_night_preview(&191, &sheriff_addr), MafiaError::WrongPhase);
+
+    let mut targets = [PASS_TARGET; 8];
+    targets[sheriff as usize] = suspect;
+    commit_all(&env, &client, 191, &players, &targets);
+    assert_mafia_error(&client.try_my_night_preview(&191, &sheriff_addr), MafiaError::NotYetReady);
+    client.reveal_action(&191, &sheriff_addr, &suspect, &(sheriff as u64));
+    assert_eq!(client.my_night_preview(&191, &sheriff_addr), Some(suspect));
+
+    // Another player learns nothing of the sheriff's pick: only their own (unrevealed) slot counts.
+    assert_mafia_error(&client.try_my_night_preview(&191, &villager_addr), MafiaError::NotYetReady);
+    client.reveal_action(&191, &villager_addr, &PASS_TARGET, &(villager as u64));
+    assert_eq!(client.my_night_preview(&191, &villager_addr), None);
+    assert_mafia_error(&client.try_my_night_preview(&191, &Address::generate(&env)), MafiaError::NotInGame);
+}
+
+// ============================================================================
+// Commitment Validation Tests
+// ============================================================================
+
+#[test]
+fn test_all_zero_commitment_rejected() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 192, 2);
+    let creator = players.get(0).unwrap();
+    client.begin_game(&192, &creator);
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    assert_mafia_error(&client.try_submit_commitment(&192, &creator, &zero), MafiaError::InvalidCommitment);
+    assert!(!client.get_game(&192).unwrap().slots.get(0).unwrap().submitted);
+    client.submit_commitment(&192, &creator, &commitment(&env, PASS_TARGET, 0));
+    assert_mafia_error(&client.try_update_commitment(&192, &creator, &zero), MafiaError::InvalidCommitment);
+}
+
+// ============================================================================
+// Confidant Tests
+// ============================================================================
+
+#[test]
+fn test_confidant_learns_one_mafia_member() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 193, 8);
+    configure(&client, 193, &players.get(0).unwrap(), RULE_CONFIDANT);
+    assert_mafia_error(&client.try_get_confidant_intel(&193, &players.get(0).unwrap()), MafiaError::WrongPhase);
+    client.begin_game(&193, &players.get(0).unwrap());
+
+    let confidants = slots_with_role(&client, 193, ROLE_CONFIDANT);
+    assert_eq!(confidants.len(), 1);
+    let confidant = confidants.get(0).unwrap();
+    let intel = client.get_confidant_intel(&193, &players.get(confidant).unwrap()).unwrap();
+    assert_eq!(client.get_game(&193).unwrap().slots.get(intel).unwrap().role, ROLE_MAFIA);
+    for i in 0..MAX_PLAYERS {
+        if i == confidant { continue; }
+        assert_eq!(client.get_confidant_intel(&193, &players.get(i).unwrap()), None);
+    }
+}
+
+// ============================================================================
+// Host Succession Tests
+// ============================================================================
+
+#[test]
+fn test_host_powers_pass_when_creator_dies() {
+    let (env, client, players) = begun_game_with(194, 8, GameConfig { grace_ledgers: 10, ..default_config() });
+    let creator = players.get(0).unwrap();
+    let mafia = slots_with_role(&client, 194, ROLE_MAFIA);
+    // The creator's seat is killed, or lynched if the creator is mafia.
+    if mafia.contains(0) {
+        run_night(&env, &client, 194, &players, &[PASS_TARGET; 8]);
+        run_day(&client, 194, &players, &[0; 8]);
+    } else {
+        run_night(&env, &client, 194, &players, &mafia_kill(&client, 194, 0));
+    }
+    let heir = players.get(1).unwrap();
+    let event = HostChanged { session_id: 194, host: heir.clone() };
+    assert_eq!(env.events().all().filter_by_contract(&client.address), [event.to_xdr(&env, &client.address)]);
+    assert_eq!(client.get_game(&194).unwrap().creator, heir);
+    assert_mafia_error(&client.try_extend_deadline(&194, &creator, &5), MafiaError::NotCreator);
+    client.extend_deadline(&194, &heir, &5);
+}
+
+#[test]
+fn test_host_lapses_to_admin_when_no_human_survives() {
+    let (env, client, _) = begun_game(195, 1, 0);
+    force_dead(&env, &client, 195, &Vec::from_array(&env, [0u32]));
+    client.resolve(&195);
+    client.resolve(&195);
+    assert_eq!(client.get_game(&195).unwrap().creator, client.get_admin());
+}
+
+// ============================================================================
+// Day Discussion Tests
+// ============================================================================
+
+#[test]
+fn test_votes_rejected_until_discussion_ends() {
+    let (env, client, players) = begun_game_with(196, 2, GameConfig { grace_ledgers: 10, discussion_ledgers: 30, ..default_config() });
+    let creator = players.get(0).unwrap();
+    commit_all(&env, &client, 196, &players, &[PASS_TARGET; 8]);
+    reveal_all(&client, 196, &players, &[PASS_TARGET; 8]);
+    client.resolve(&196);
+
+    let game = client.get_game(&196).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.votes_open_at, env.ledger().sequence() + 30);
+    assert_mafia_error(&client.try_submit_action(&196, &creator, &PASS_TARGET), MafiaError::DiscussionOpen);
+    assert!(client.decision_space(&196, &creator).legal_targets.is_empty());
+    assert_mafia_error(&client.try_resolve(&196), MafiaError::NotYetReady);
+    // The voting grace runs from the end of discussion, not from dawn.
+    assert_eq!(client.get_phase_deadline(&196), Some(game.votes_open_at + 10));
+
+    advance_ledgers(&env, 30);
+    client.submit_action(&196, &creator, &PASS_TARGET);
+    client.submit_action(&196, &players.get(1).unwrap(), &PASS_TARGET);
+    client.resolve(&196);
+    assert_eq!(client.get_game(&196).unwrap().phase, PHASE_NIGHT_COMMIT);
+}
+
+// ============================================================================
+// Judgment Tests
+// ============================================================================
+
+/// Day 1 under RULE_JUDGMENT with the whole table accusing slot 2.
+fn open_judgment(session_id: u32) -> (Env, MafiaDuelContractClient<'static>, Vec<Address>) {
+    let (env, client, players) = begun_game(session_id, 8, RULE_JUDGMENT);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    run_day(&client, session_id, &players, &[2; 8]);
+    (env, client, players)
+}
+
+#[test]
+fn test_judgment_acquits_without_guilty_majority() {
+    let (env, client, players) = open_judgment(197);
+    let game = client.get_game(&197).unwrap();
+    assert_eq!(game.phase, PHASE_DAY);
+    assert_eq!(game.accused, Vec::from_array(&env, [2u32]));
+    assert!(game.slots.iter().all(|s| s.alive && !s.submitted));
+    assert_mafia_error(&client.try_submit_action(&197, &players.get(0).unwrap(), &5), MafiaError::InvalidTarget);
+
+    // A 4-4 split is not a strict majority of the eight living.
+    let p = PASS_TARGET;
+    run_day(&client, 197, &players, &[2, 2, p, 2, 2, p, p, p]);
+    let game = client.get_game(&197).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert!(game.last_voted_out.is_empty());
+    assert!(game.accused.is_empty());
+    assert!(game.slots.iter().all(|s| s.alive));
+}
+
+#[test]
+fn test_judgment_lynches_on_guilty_majority() {
+    let (env, client, players) = open_judgment(198);
+    let p = PASS_TARGET;
+    run_day(&client, 198, &players, &[2, 2, p, 2, 2, 2, p, p]);
+    let game = client.get_game(&198).unwrap();
+    assert_eq!(game.phase, PHASE_NIGHT_COMMIT);
+    assert_eq!(game.last_voted_out, Vec::from_array(&env, [2u32]));
+    assert!(!game.slots.get(2).unwrap().alive);
+    assert!(game.accused.is_empty());
+}
+
+// ============================================================================
+// Stake Timing Tests
+// ============================================================================
+
+#[test]
+fn test_rejected_stake_rolls_back_begin() {
+    let (env, client, hub) = setup_test();
+    let players = seat_humans(&env, &client, 204, 4);
+    let creator = players.get(0).unwrap();
+    client.set_hub(&env.register(RejectingGameHub, ()));
+    assert!(client.try_begin_game(&204, &creator).is_err());
+    let game = client.get_game(&204).unwrap();
+    assert_eq!(game.phase, PHASE_LOBBY);
+    assert_eq!(game.human_count, 4);
+    assert_eq!(game.day, 0);
+
+    client.set_hub(&hub);
+    client.begin_game(&204, &creator);
+    assert_eq!(MockGameHubClient::new(&env, &hub).started_points(&204), Some((100, 100)));
+}
+
+#[test]
+fn test_retried_begin_neither_restarts_hub_nor_redeals() {
+    let (env, client, hub) = setup_test();
+    let players = seat_humans(&env, &client, 218, 8);
+    let creator = players.get(0).unwrap();
+    client.configure_game(&218, &creator, &GameConfig { rules: RULE_SHUFFLE_SEATS, ..default_config() });
+    client.begin_game(&218, &creator);
+    let started = client.get_game(&218).unwrap();
+    advance_ledgers(&env, 1);
+    assert_mafia_error(&client.try_begin_game(&218, &creator), MafiaError::WrongPhase);
+    let game = client.get_game(&218).unwrap();
+    assert_eq!((game.phase, game.day), (started.phase, started.day));
+    for i in 0..MAX_PLAYERS {
+        let (now, then) = (game.slots.get(i).unwrap(), started.slots.get(i).unwrap());
+        assert_eq!((now.addr, now.role), (then.addr, then.role));
+    }
+    assert_eq!(MockGameHubClient::new(&env, &hub).start_count(&218), 1);
+}
+
+// ============================================================================
+// Fee Tests
+// ============================================================================
+
+/// A fee token and treasury with `create_fee` 10 and `action_fee` 3. Returns (token, treasury).
+fn setup_fees(env: &Env, client: &MafiaDuelContractClient) -> (Address, Address) {
+    let token = env.register_stellar_asset_contract_v2(Address::generate(env)).address();
+    let treasury = Address::generate(env);
+    client.set_treasury(&treasury);
+    client.set_fees(&Some(Fees { token: token.clone(), create_fee: 10, action_fee: 3 }));
+    (token, treasury)
+}
+
+#[test]
+fn test_fees_route_to_treasury() {
+    let (env, client, _hub) = setup_test();
+    let (token, treasury) = setup_fees(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let creator = Address::generate(&env);
+    let second = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&creator, &20);
+
+    client.create_game(&219, &creator, &100);
+    client.join_game(&219, &second);
+    client.begin_game(&219, &creator);
+    client.submit_commitment(&219, &creator, &commitment(&env, PASS_TARGET, 0));
+    assert_eq!((balance(&creator), balance(&treasury)), (7, 13));
+    // Fees stay out of the pot.
+    assert_eq!(client.get_game(&219).unwrap().wager, 100);
+
+    let broke = client.try_submit_commitment(&219, &second, &commitment(&env, PASS_TARGET, 1));
+    assert_mafia_error(&broke, MafiaError::InsufficientFee);
+    assert!(!client.get_game(&219).unwrap().slots.get(1).unwrap().submitted);
+    assert_mafia_error(&client.try_create_game(&220, &second, &100), MafiaError::InsufficientFee);
+    assert!(client.get_game(&220).is_none());
+}
+
+#[test]
+fn test_fees_default_to_free() {
+    let (env, client, _hub) = setup_test();
+    assert_eq!(client.get_fees(), None);
+    let players = seat_humans(&env, &client, 221, 2);
+    client.begin_game(&221, &players.get(0).unwrap());
+    commit_all(&env, &client, 221, &players, &[PASS_TARGET; 8]);
+    assert_eq!(client.get_game(&221).unwrap().phase, PHASE_NIGHT_REVEAL);
+
+    let negative = Fees { token: Address::generate(&env), create_fee: -1, action_fee: 0 };
+    assert_mafia_error(&client.try_set_fees(&Some(negative)), MafiaError::InvalidConfig);
+}
+
+// ============================================================================
+// Action State Tests
+// ============================================================================
+
+#[test]
+fn test_action_state_walks_commit_then_reveal() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 222, 8);
+    let me = players.get(0).unwrap();
+    client.begin_game(&222, &me);
+    let state = |phase, committed, revealed, locked, next_step| ActionState { phase, committed, revealed, locked, ability_charges: 1, next_step };
+    assert_eq!(client.my_action_state(&222, &me), state(PHASE_NIGHT_COMMIT, false, false, false, STEP_COMMIT));
+
+    client.submit_commitment(&222, &me, &commitment(&env, PASS_TARGET, 0));
+    client.lock_commitment(&222, &me);
+    assert_eq!(client.my_action_state(&222, &me), state(PHASE_NIGHT_COMMIT, true, false, true, STEP_NONE));
+
+    commit_all_remaining(&env, &client, 222, &players);
+    assert_eq!(client.my_action_state(&222, &me), state(PHASE_NIGHT_REVEAL, true, false, true, STEP_REVEAL));
+
+    client.reveal_action(&222, &me, &PASS_TARGET, &0);
+    assert_eq!(client.my_action_state(&222, &me), state(PHASE_NIGHT_REVEAL, true, true, true, STEP_NONE));
+
+    reveal_all_except(&client, 222, &players, 0);
+    client.resolve(&222);
+    assert_eq!(client.my_action_state(&222, &me), state(PHASE_DAY, false, false, false, STEP_VOTE));
+    assert_mafia_error(&client.try_my_action_state(&222, &Address::generate(&env)), MafiaError::NotInGame);
+}
+
+#[test]
+fn test_vote_guidance_waits_for_announce_and_resume() {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, 244, 8);
+    let me = players.get(0).unwrap();
+    configure(&client, 244, &me, RULE_MORNING_ANNOUNCE);
+    client.begin_game(&244, &me);
+    run_night(&env, &client, 244, &players, &[PASS_TARGET; 8]);
+    let can_vote = || {
+        let space = client.decision_space(&244, &me);
+        let step = client.my_action_state(&244, &me).next_step;
+        assert_eq!(space.can_pass, step == STEP_VOTE);
+        assert_eq!(space.legal_targets.is_empty(), step != STEP_VOTE);
+        step == STEP_VOTE
+    };
+    assert!(!can_vote());
+    client.announce(&244, &me);
+    assert!(can_vote());
+    client.pause_game(&244, &me);
+    assert!(!can_vote());
+    client.resume_game(&244, &me);
+    assert!(can_vote());
+}
+
+// ============================================================================
+// Lone Faction Tests
+// ============================================================================
+
+/// Serial-killer game where everyone outside `team` dies before night one resolves. The
+/// parity rules that usually hold a win back are all on, and none of them applies.
+fn lone_faction_winner(session_id: u32, team: u32) -> Option<u32> {
+    let (env, client, _hub) = setup_test();
+    let players = seat_humans(&env, &client, session_id, 8);
+    let rules = RULE_SERIAL_KILLER | RULE_NEUTRAL_BLOCKS_PARITY | RULE_DAY_BEFORE_PARITY;
+    configure(&client, session_id, &players.get(0).unwrap(), rules);
+    client.begin_game(&session_id, &players.get(0).unwrap());
+    let game = client.get_game(&session_id).unwrap();
+    let mut others = Vec::new(&env);
+    for i in 0..MAX_PLAYERS {
+        let role = game.slots.get(i).unwrap().role;
+        let on_team = match team {
+            TEAM_MAFIA => role == ROLE_MAFIA,
+            TEAM_SERIAL_KILLER => role == ROLE_SERIAL_KILLER,
+            _ => role != ROLE_MAFIA && role != ROLE_SERIAL_KILLER,
+        };
+        if !on_team { others.push_back(i); }
+    }
+    force_dead(&env, &client, session_id, &others);
+    run_night(&env, &client, session_id, &players, &[PASS_TARGET; 8]);
+    let game = client.get_game(&session_id).unwrap();
+    assert_eq!(game.phase, PHASE_OVER);
+    game.winner
+}
+
+#[test]
+fn test_all_town_survivors_win() {
+    assert_eq!(lone_faction_winner(223, TEAM_TOWN), Some(TEAM_TOWN));
+}
+
+#[test]
+fn test_all_neutral_survivors_win() {
+    assert_eq!(lone_faction_winner(224, TEAM_SERIAL_KILLER), Some(TEAM_SERIAL_KILLER));
+}
+
+#[test]
+fn test_all_mafia_survivors_win_without_waiting_for_day() {
+    assert_eq!(lone_faction_winner(225, TEAM_MAFIA), Some(TEAM_MAFIA));
+}
+
+// ============================================================================
+// Stake Timing Tests
+// ============================================================================
+
+/// Fees that charge nothing, so their token serves only as the escrow token.
+fn setup_escrow(env: &Env, client: &MafiaDuelContractClient) -> Address {
+    let token = env.register_stellar_asset_contract_v2(Address::generate(env)).address();
+    client.set_treasury(&Address::generate(env));
+    client.set_fees(&Some(Fees { token: token.clone(), create_fee: 0, action_fee: 0 }));
+    token
+}
+
+#[test]
+fn test_stake_timing_needs_an_escrow_token() {
+    let (env, client, _hub) = setup_test();
+    let creator = Address::generate(&env);
+    let on_join = GameConfig { stake_timing: STAKE_ON_JOIN, ..default_config() };
+    assert_mafia_error(&client.try_create_game_with_config(&234, &creator, &100, &on_join), MafiaError::InvalidConfig);
+    setup_escrow(&env, &client);
+    let bad = GameConfig { stake_timing: STAKE_ON_BEGIN + 1, ..default_config() };
+    assert_mafia_error(&client.try_create_game_with_config(&234, &creator, &100, &bad), MafiaError::InvalidConfig);
+}
+
+#[test]
+fn test_stake_on_join_escrows_and_refunds() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let people: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    for p in people.iter() { token::StellarAssetClient::new(&env, &token).mint(p, &100); }
+    let [creator, second, third, fresh] = people.clone();
+    let config = GameConfig { stake_timing: STAKE_ON_JOIN, ..default_config() };
+
+    client.create_game_with_config(&235, &creator, &100, &config);
+    client.join_game(&235, &second);
+    client.join_game(&235, &third);
+    assert_eq!((balance(&creator), balance(&second), balance(&third)), (0, 0, 0));
+    assert_eq!(balance(&client.address), 300);
+
+    client.leave_game(&235, &second);
+    assert_eq!(balance(&second), 100);
+    let game = client.get_game(&235).unwrap();
+    assert_eq!((game.human_count, game.slots.get(1).unwrap().addr), (2, Some(third.clone())));
+    assert_mafia_error(&client.try_leave_game(&235, &creator), MafiaError::SeatLocked);
+
+    // The stake follows the wallet that paid it.
+    client.swap_address(&235, &third, &fresh);
+    assert_eq!((balance(&third), balance(&fresh)), (100, 0));
+
+    // Turning escrow off hands everything back.
+    client.configure_game(&235, &creator, &default_config());
+    assert_eq!((balance(&creator), balance(&fresh), balance(&client.address)), (100, 100, 0));
+}
+
+#[test]
+fn test_cancel_game_refunds_join_stakes() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let (creator, second) = (Address::generate(&env), Address::generate(&env));
+    for p in [&creator, &second] { token::StellarAssetClient::new(&env, &token).mint(p, &100); }
+    client.create_game_with_config(&236, &creator, &100, &GameConfig { stake_timing: STAKE_ON_JOIN, ..default_config() });
+    client.join_game(&236, &second);
+    assert_mafia_error(&client.try_cancel_game(&236, &second), MafiaError::NotCreator);
+
+    client.cancel_game(&236, &creator);
+    assert_eq!((balance(&creator), balance(&second), balance(&client.address)), (100, 100, 0));
+    assert!(client.get_game(&236).is_none());
+}
+
+#[test]
+fn test_admin_clears_idle_staked_lobby() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let (creator, second) = (Address::generate(&env), Address::generate(&env));
+    for p in [&creator, &second] { token::StellarAssetClient::new(&env, &token).mint(p, &100); }
+    client.create_game_with_config(&239, &creator, &100, &GameConfig { stake_timing: STAKE_ON_JOIN, ..default_config() });
+    advance_ledgers(&env, ABANDON_LEDGERS - 1);
+    client.join_game(&239, &second);
+    // The clock runs from the last seat taken, not from creation.
+    advance_ledgers(&env, ABANDON_LEDGERS - 1);
+    assert_mafia_error(&client.try_admin_clear_session(&239), MafiaError::NotYetReady);
+
+    advance_ledgers(&env, 1);
+    client.admin_clear_session(&239);
+    assert_eq!((balance(&creator), balance(&second), balance(&client.address)), (100, 100, 0));
+    assert!(client.get_game(&239).is_none());
+}
+
+#[test]
+fn test_create_and_join_escrows_both_stakes() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let (creator, second, broke) = (Address::generate(&env), Address::generate(&env), Address::generate(&env));
+    for p in [&creator, &second] { token::StellarAssetClient::new(&env, &token).mint(p, &100); }
+    let config = GameConfig { stake_timing: STAKE_ON_JOIN, ..default_config() };
+
+    // The second stake bounces, so the whole call rolls back, the creator's stake included.
+    let short = client.try_create_and_join(&242, &creator, &broke, &100, &config);
+    assert_mafia_error(&short, MafiaError::InsufficientStake);
+    assert!(client.get_game(&242).is_none());
+    assert_eq!(balance(&creator), 100);
+
+    client.create_and_join(&242, &creator, &second, &100, &config);
+    let game = client.get_game(&242).unwrap();
+    assert!(game.slots.get(0).unwrap().staked && game.slots.get(1).unwrap().staked);
+    assert_eq!((balance(&creator), balance(&second), balance(&client.address)), (0, 0, 200));
+}
+
+#[test]
+fn test_stake_on_begin_pulls_all_and_pays_out() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let players = seat_humans(&env, &client, 237, 8);
+    client.configure_game(&237, &players.get(0).unwrap(), &GameConfig { stake_timing: STAKE_ON_BEGIN, ..default_config() });
+    for p in players.iter() {
+        token::StellarAssetClient::new(&env, &token).mint(&p, &100);
+        token::Client::new(&env, &token).approve(&p, &client.address, &100, &10_000);
+    }
+    // Nothing moves until the game starts.
+    assert_eq!(balance(&client.address), 0);
+    client.begin_game(&237, &players.get(0).unwrap());
+    assert_eq!(balance(&client.address), 800);
+    assert!(players.iter().all(|p| balance(&p) == 0));
+
+    play_town_win(&env, &client, 237, &players);
+    assert_eq!(balance(&client.address), 0);
+    for (addr, amount) in client.get_payouts(&237).iter() { assert_eq!(balance(&addr), amount); }
+    let mafia = slots_with_role(&client, 237, ROLE_MAFIA);
+    assert!(mafia.iter().all(|m| balance(&players.get(m).unwrap()) == 0));
+}
+
+#[test]
+fn test_stake_on_begin_shortfall_rolls_back_the_start() {
+    let (env, client, _hub) = setup_test();
+    let token = setup_escrow(&env, &client);
+    let balance = |a: &Address| token::Client::new(&env, &token).balance(a);
+    let players = seat_humans(&env, &client, 238, 3);
+    client.configure_game(&238, &players.get(0).unwrap(), &GameConfig { stake_timing: STAKE_ON_BEGIN, ..default_config() });
+    for p in players.iter() { token::StellarAssetClient::new(&env, &token).mint(&p, &100); }
+    // The last player never grants the allowance, so their pull fails after the others'.
+    for i in 0..2 { token::Client::new(&env, &token).approve(&players.get(i).unwrap(), &client.address, &100, &10_000); }
+
+    assert_mafia_error(&client.try_begin_game(&238, &players.get(0).unwrap()), MafiaError::InsufficientStake);
+    assert_eq!(client.get_game(&238).unwrap().phase, PHASE_LOBBY);
+    assert!(players.iter().all(|p| balance(&p) == 100));
+    assert_eq!(balance(&client.address), 0);
+
+    token::Client::new(&env, &token).approve(&players.get(2).unwrap(), &client.address, &100, &10_000);
+    client.begin_game(&238, &players.get(0).unwrap());
+    assert_eq!(balance(&client.address), 300);
+}